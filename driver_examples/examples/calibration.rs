@@ -1,7 +1,6 @@
-mod tui;
+use driver_examples::tui::SledTerminalDisplay;
 use palette::rgb::Rgb;
 use spatial_led::{Sled, SledError, Vec2};
-use tui::SledTerminalDisplay;
 
 fn main() -> Result<(), SledError> {
     let mut sled = Sled::<Rgb>::new("./complex_room.yap")?;
@@ -0,0 +1,104 @@
+//! Adalight: frames of RGB pixels over a serial port, as understood by the Adalight
+//! sketches and most of the Arduino and ESP firmware built on them.
+
+use std::{io::Write, time::Duration};
+
+use serialport::SerialPort;
+use spatial_led::{SledError, SledResult};
+
+use crate::{
+    encode::{Encoder, EncoderConfig},
+    sink::{Frame, LedSink},
+};
+
+pub const DEFAULT_BAUD: u32 = 115_200;
+/// The most LEDs a frame can carry, since the count is sent in 16 bits.
+pub const MAX_LEDS: usize = u16::MAX as usize + 1;
+
+const MAGIC: &[u8; 3] = b"Ada";
+const HEADER_LENGTH: usize = 6;
+
+/// The header for a frame of `leds` LEDs: the magic word, the count less one, high byte
+/// first, and a checksum of the count.
+pub fn header(leds: usize) -> [u8; HEADER_LENGTH] {
+    let [hi, lo] = ((leds.clamp(1, MAX_LEDS) - 1) as u16).to_be_bytes();
+    [MAGIC[0], MAGIC[1], MAGIC[2], hi, lo, hi ^ lo ^ 0x55]
+}
+
+/// A whole frame: the header followed by RGB for each LED.
+pub fn frame(data: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_LENGTH + data.len());
+    frame.extend_from_slice(&header(data.len() / 3));
+    frame.extend_from_slice(data);
+    frame
+}
+
+/// Reads the first frame in `buffer`, returning its RGB data along with how many bytes
+/// were used, or `None` if no whole frame has arrived yet.
+///
+/// Like the firmware, anything before a header with a good checksum is skipped, so a
+/// reader that starts partway through a frame catches up on the next one.
+pub fn parse(buffer: &[u8]) -> Option<(&[u8], usize)> {
+    let start = buffer
+        .windows(HEADER_LENGTH)
+        .position(|window| window[..3] == *MAGIC && window[3] ^ window[4] ^ 0x55 == window[5])?;
+    let header = &buffer[start..start + HEADER_LENGTH];
+    let leds = u16::from_be_bytes([header[3], header[4]]) as usize + 1;
+    let end = start + HEADER_LENGTH + leds * 3;
+    let data = buffer.get(start + HEADER_LENGTH..end)?;
+    Some((data, end))
+}
+
+/// Sends frames to a microcontroller running Adalight firmware over USB serial.
+///
+/// Every byte takes ten bits on the wire, so the baud rate caps the frame rate: at
+/// 115200, 100 LEDs go out about 38 times a second.
+pub struct AdalightSink {
+    port: Box<dyn SerialPort>,
+    encoder: Encoder,
+    data: Vec<u8>,
+}
+
+impl AdalightSink {
+    /// Opens the serial port at `path`, such as `/dev/ttyUSB0` or `COM3`.
+    pub fn open(path: &str, baud: u32, output: &EncoderConfig) -> Result<Self, SledError> {
+        let port = serialport::new(path, baud)
+            // long enough for a slow baud rate, short enough to notice an unplugged board
+            .timeout(Duration::from_secs(1))
+            .open()
+            .map_err(|e| SledError::new(format!("Can't open `{}`: {}", path, e)))?;
+        Ok(AdalightSink {
+            port,
+            encoder: Encoder::new(output),
+            data: vec![],
+        })
+    }
+
+    pub fn baud_rate(&self) -> Result<u32, SledError> {
+        self.port.baud_rate().map_err(SledError::from_error)
+    }
+}
+
+impl LedSink for AdalightSink {
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        if frame.colors.len() > MAX_LEDS {
+            return SledError::new(format!(
+                "Adalight can't send {} LEDs in one frame.",
+                frame.colors.len()
+            ))
+            .as_err();
+        }
+        if frame.colors.is_empty() {
+            return Ok(());
+        }
+
+        self.data.clear();
+        self.data.extend_from_slice(&header(frame.colors.len()));
+        for pixel in self.encoder.encode(frame.colors.iter()) {
+            self.data.extend_from_slice(&pixel[..3]);
+        }
+        self.port
+            .write_all(&self.data)
+            .map_err(SledError::from_error)
+    }
+}
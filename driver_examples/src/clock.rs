@@ -0,0 +1,82 @@
+use std::{
+    cell::Cell,
+    ops::{AddAssign, SubAssign},
+    sync::OnceLock,
+    time::Duration,
+};
+
+use spatial_led::{driver::CustomDriver, time::Instant};
+
+/// A driver running on `Clock`, which every effect in this crate is built as.
+pub type Driver<COLOR> = CustomDriver<Clock, COLOR>;
+
+static EPOCH: OnceLock<std::time::Instant> = OnceLock::new();
+
+thread_local! {
+    static MANUAL: Cell<Option<i128>> = const { Cell::new(None) };
+}
+
+/// An instant that normally follows the system clock, but can be frozen so that time
+/// only moves when `advance` is called.
+///
+/// Freezing is per thread, so drivers stepped on a frozen thread see exactly the
+/// timesteps they're given, no matter how long each frame really took.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Clock {
+    nanos: i128,
+}
+
+impl Clock {
+    fn real_nanos() -> i128 {
+        EPOCH
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_nanos() as i128
+    }
+}
+
+impl Instant for Clock {
+    fn now() -> Self {
+        let nanos = MANUAL.get().unwrap_or_else(Clock::real_nanos);
+        Clock { nanos }
+    }
+
+    fn elapsed(&self) -> Duration {
+        let nanos = (Clock::now().nanos - self.nanos).max(0);
+        Duration::from_nanos(nanos as u64)
+    }
+}
+
+impl AddAssign<Duration> for Clock {
+    fn add_assign(&mut self, rhs: Duration) {
+        self.nanos += rhs.as_nanos() as i128;
+    }
+}
+
+impl SubAssign<Duration> for Clock {
+    fn sub_assign(&mut self, rhs: Duration) {
+        self.nanos -= rhs.as_nanos() as i128;
+    }
+}
+
+/// Stops time on this thread. Drivers mounted afterwards start at zero, and only see
+/// time pass through `advance`.
+pub fn freeze() {
+    MANUAL.set(Some(0));
+}
+
+/// Goes back to following the system clock on this thread.
+pub fn unfreeze() {
+    MANUAL.set(None);
+}
+
+pub fn is_frozen() -> bool {
+    MANUAL.get().is_some()
+}
+
+/// Moves a frozen clock forward by `step`. Does nothing if the clock isn't frozen.
+pub fn advance(step: Duration) {
+    if let Some(nanos) = MANUAL.get() {
+        MANUAL.set(Some(nanos + step.as_nanos() as i128));
+    }
+}
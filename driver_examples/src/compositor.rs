@@ -0,0 +1,191 @@
+use std::{fmt, str::FromStr};
+
+use palette::rgb::Rgb;
+use rand::Rng;
+use spatial_led::{
+    driver::{Data, Time},
+    Sled, SledResult,
+};
+
+use crate::{clock::Driver, rng};
+
+/// How a layer is combined with everything stacked beneath it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LayerBlend {
+    /// Sums each channel, brightening wherever both layers are lit.
+    Add,
+    /// Like add, but eases off as channels approach full brightness.
+    Screen,
+    /// Darkens the layers beneath, leaving them untouched where this layer is white.
+    Multiply,
+    /// Keeps the brighter of each channel.
+    Max,
+    /// Paints over the layers beneath, using the layer's opacity as its alpha.
+    #[default]
+    Alpha,
+}
+
+impl LayerBlend {
+    /// Combines `layer` onto `base`, then fades between the two by `opacity`.
+    pub fn apply(self, base: Rgb, layer: Rgb, opacity: f32) -> Rgb {
+        let blended = match self {
+            LayerBlend::Add => base + layer,
+            LayerBlend::Screen => base + layer - base * layer,
+            LayerBlend::Multiply => base * layer,
+            LayerBlend::Max => Rgb::new(
+                base.red.max(layer.red),
+                base.green.max(layer.green),
+                base.blue.max(layer.blue),
+            ),
+            LayerBlend::Alpha => layer,
+        };
+        base + (blended - base) * opacity
+    }
+}
+
+impl FromStr for LayerBlend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "add" => Ok(LayerBlend::Add),
+            "screen" => Ok(LayerBlend::Screen),
+            "multiply" => Ok(LayerBlend::Multiply),
+            "max" => Ok(LayerBlend::Max),
+            "alpha" => Ok(LayerBlend::Alpha),
+            _ => Err(format!(
+                "Unknown layer blend `{}`. Expected add, screen, multiply, max or alpha.",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LayerBlend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayerBlend::Add => write!(f, "add"),
+            LayerBlend::Screen => write!(f, "screen"),
+            LayerBlend::Multiply => write!(f, "multiply"),
+            LayerBlend::Max => write!(f, "max"),
+            LayerBlend::Alpha => write!(f, "alpha"),
+        }
+    }
+}
+
+/// A driver in a `Compositor`'s stack, along with how it's blended onto the ones below.
+pub struct Layer {
+    driver: Driver<Rgb>,
+    pub blend: LayerBlend,
+    pub opacity: f32,
+}
+
+impl Layer {
+    pub fn driver(&self) -> &Driver<Rgb> {
+        &self.driver
+    }
+
+    pub fn driver_mut(&mut self) -> &mut Driver<Rgb> {
+        &mut self.driver
+    }
+}
+
+impl fmt::Debug for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Layer")
+            .field("blend", &self.blend)
+            .field("opacity", &self.opacity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Stacks several drivers on top of each other, bottom layer first.
+///
+/// Each layer is mounted on its own copy of the room layout, so effects that overwrite
+/// every LED (like ripples' `set_all`) only ever clear their own buffer. The layers are
+/// then blended together into the compositor's output.
+///
+/// ```rust, ignore
+/// let driver = Compositor::new()
+///     .with_layer(embers::build_driver(), LayerBlend::Alpha, 1.0)
+///     .with_layer(ripples::build_driver(), LayerBlend::Screen, 0.8)
+///     .build_driver();
+/// ```
+#[derive(Debug, Default)]
+pub struct Compositor {
+    layers: Vec<Layer>,
+}
+
+impl Compositor {
+    pub fn new() -> Self {
+        Compositor::default()
+    }
+
+    pub fn with_layer(mut self, driver: Driver<Rgb>, blend: LayerBlend, opacity: f32) -> Self {
+        self.push(driver, blend, opacity);
+        self
+    }
+
+    pub fn push(&mut self, driver: Driver<Rgb>, blend: LayerBlend, opacity: f32) {
+        self.layers.push(Layer {
+            driver,
+            blend,
+            opacity: opacity.clamp(0.0, 1.0),
+        });
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn layers_mut(&mut self) -> &mut [Layer] {
+        &mut self.layers
+    }
+
+    /// Wraps the stack up as a single driver, so it can be crossfaded, played from a
+    /// playlist or mounted like any other effect.
+    ///
+    /// The layers are kept in the driver's `Data` under `layers`.
+    pub fn build_driver(self) -> Driver<Rgb> {
+        let mut driver = Driver::new();
+        driver.data_mut().set("layers", self);
+
+        driver.set_startup_commands(startup);
+        driver.set_compute_commands(compute);
+        driver.set_draw_commands(draw);
+        driver
+    }
+}
+
+fn startup(sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
+    let mut rng = rng::init(data)?;
+    let compositor: &mut Compositor = data.get_mut("layers")?;
+    for layer in &mut compositor.layers {
+        // give each layer its own seed, so stacking an effect on itself isn't a no-op
+        rng::set_seed(layer.driver.data_mut(), rng.gen());
+        layer.driver.mount(sled.clone());
+    }
+    Ok(())
+}
+
+fn compute(_sled: &Sled<Rgb>, data: &mut Data, _time: &Time) -> SledResult {
+    let compositor: &mut Compositor = data.get_mut("layers")?;
+    for layer in &mut compositor.layers {
+        layer.driver.step();
+    }
+    Ok(())
+}
+
+fn draw(sled: &mut Sled<Rgb>, data: &Data, _time: &Time) -> SledResult {
+    let compositor: &Compositor = data.get("layers")?;
+    sled.set_all(Rgb::new(0.0, 0.0, 0.0));
+    for layer in &compositor.layers {
+        let mut colors = layer.driver.colors();
+        sled.for_each(|led| {
+            if let Some(color) = colors.next() {
+                led.color = layer.blend.apply(led.color, *color, layer.opacity);
+            }
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+use palette::rgb::Rgb;
+use spatial_led::Sled;
+
+/// How much of a color is left after `delta` when it halves every `half_life` seconds.
+pub fn factor(delta: Duration, half_life: f32) -> f32 {
+    if half_life <= 0.0 {
+        return 0.0;
+    }
+    0.5f32.powf(delta.as_secs_f32() / half_life)
+}
+
+/// Fades everything drawn on previous frames towards black, leaving trails that are
+/// the same length at any frame rate. Effects that want persistence call this at the
+/// top of their draw commands instead of clearing the sled.
+pub fn fade(sled: &mut Sled<Rgb>, delta: Duration, half_life: f32) {
+    let factor = factor(delta, half_life);
+    sled.for_each(|led| led.color *= factor);
+}
@@ -0,0 +1,77 @@
+use spatial_led::{
+    driver::{Data, Time},
+    Sled, SledResult,
+};
+
+use palette::rgb::Rgb;
+
+use crate::{
+    clock::Driver,
+    params::{self, ParamSpec},
+};
+
+use std::f32::consts::TAU;
+const INV_TAU: f32 = 1.0 / TAU;
+
+const GREEN_RADIUS: f32 = 2.33;
+const GREEN_COUNT: usize = 64;
+const GREEN: Rgb = Rgb::new(0.6, 0.93, 0.762);
+
+const BLUE_RADIUS: f32 = 3.0;
+const BLUE_COUNT: usize = 96;
+const BLUE: Rgb = Rgb::new(0.4, 0.51, 0.93);
+
+const TRAIL_RADIUS: f32 = 1.2;
+
+pub const PARAMS: &[ParamSpec] = &[
+    ParamSpec::float("green_radius", GREEN_RADIUS, 0.1..=20.0),
+    ParamSpec::count("green_count", GREEN_COUNT, 0..=1024),
+    ParamSpec::float("blue_radius", BLUE_RADIUS, 0.1..=20.0),
+    ParamSpec::count("blue_count", BLUE_COUNT, 0..=1024),
+    ParamSpec::float("trail_radius", TRAIL_RADIUS, 0.1..=20.0),
+];
+
+pub fn build_driver() -> Driver<Rgb> {
+    let mut driver = Driver::new();
+    driver.set_startup_commands(startup);
+    driver.set_draw_commands(draw);
+    driver
+}
+
+fn startup(_sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
+    params::store_defaults(data, PARAMS);
+    Ok(())
+}
+
+fn draw(sled: &mut Sled<Rgb>, data: &Data, time_info: &Time) -> SledResult {
+    let elapsed = time_info.elapsed.as_secs_f32();
+    let green_count = *data.get::<usize>("green_count")?;
+    let blue_count = *data.get::<usize>("blue_count")?;
+
+    let inner_time_scale = elapsed / data.get::<f32>("green_radius")?;
+    let outer_time_scale = elapsed / data.get::<f32>("blue_radius")?;
+
+    // speckle in swirling green points
+    for i in 0..green_count {
+        let angle = inner_time_scale + (TAU / green_count as f32) * i as f32 % TAU;
+        sled.modulate_at_angle(angle, |led| led.color + GREEN);
+    }
+
+    // speckle in swirling blue points
+    for i in 0..blue_count {
+        let angle = outer_time_scale + (TAU / blue_count as f32) * i as f32 % TAU;
+        sled.modulate_at_angle(angle, |led| led.color + BLUE);
+    }
+
+    // brighten or darken points depending on time and angle to simulate a sweeping
+    // trail thing.
+    let radar_time_scale = elapsed / data.get::<f32>("trail_radius")?;
+    let angle = (radar_time_scale % TAU) + TAU;
+    sled.map(|led| {
+        let da = (led.angle() + angle) % TAU;
+        let fac = 1.0 - (da * INV_TAU).powf(1.25);
+        led.color * fac
+    });
+
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod comet;
+pub mod embers;
+pub mod ripples;
+pub mod scan;
+pub mod warpspeed;
@@ -1,17 +1,28 @@
 use spatial_led::{
-    driver::{Data, Driver, Time},
+    driver::{Data, Time},
     Sled, SledResult, Vec2,
 };
 
 use palette::rgb::Rgb;
 
+use crate::{
+    clock::Driver,
+    params::{self, ParamSpec},
+    rng,
+};
+
 use rand::Rng;
 use std::ops::Range;
 
 const MAX_RIPPLES: usize = 12;
 const MAX_RADIUS: f32 = 12.0;
 const FEATHERING: f32 = 0.15;
-const INV_F: f32 = 1.0 / FEATHERING;
+
+pub const PARAMS: &[ParamSpec] = &[
+    ParamSpec::count("max_ripples", MAX_RIPPLES, 0..=256),
+    ParamSpec::float("max_radius", MAX_RADIUS, 1.0..=100.0),
+    ParamSpec::float("feathering", FEATHERING, 0.01..=5.0),
+];
 
 pub fn build_driver() -> Driver<Rgb> {
     let mut driver = Driver::new();
@@ -19,21 +30,25 @@ pub fn build_driver() -> Driver<Rgb> {
     driver.set_startup_commands(startup);
     driver.set_compute_commands(compute);
     driver.set_draw_commands(draw);
-    return driver;
+    driver
 }
 
 fn startup(sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
+    params::store_defaults(data, PARAMS);
+    let mut rng = rng::init(data)?;
     let sled_bounds = sled.domain();
+    let max_ripples = *data.get::<usize>("max_ripples")?;
 
     let radii = data.store::<Vec<f32>>("radii", vec![]);
-    for _ in 0..MAX_RIPPLES {
-        radii.push(rand_init_radius());
+    for _ in 0..max_ripples {
+        radii.push(rand_init_radius(&mut rng));
     }
 
     let positions = data.store::<Vec<Vec2>>("positions", vec![]);
-    for _ in 0..MAX_RIPPLES {
-        positions.push(rand_point_in_range(&sled_bounds));
+    for _ in 0..max_ripples {
+        positions.push(rand_point_in_range(&mut rng, &sled_bounds));
     }
+    rng::store(data, rng)?;
 
     data.set::<Vec<Rgb>>(
         "colors",
@@ -57,11 +72,21 @@ fn startup(sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
 fn compute(sled: &Sled<Rgb>, data: &mut Data, time_info: &Time) -> SledResult {
     let delta = time_info.delta.as_secs_f32();
     let bounds = sled.domain();
-    for i in 0..MAX_RIPPLES {
+    let max_ripples = *data.get::<usize>("max_ripples")?;
+    let max_radius = *data.get::<f32>("max_radius")?;
+    let mut rng = rng::take(data)?;
+
+    // follow along if the number of ripples was changed at runtime
+    data.get_mut::<Vec<f32>>("radii")?
+        .resize_with(max_ripples, || rand_init_radius(&mut rng));
+    data.get_mut::<Vec<Vec2>>("positions")?
+        .resize_with(max_ripples, || rand_point_in_range(&mut rng, &bounds));
+
+    for i in 0..max_ripples {
         let radius: f32 = data.get::<Vec<f32>>("radii")?[i];
-        if radius > MAX_RADIUS {
-            let new_pos = rand_point_in_range(&bounds);
-            let new_radius = rand_init_radius();
+        if radius > max_radius {
+            let new_pos = rand_point_in_range(&mut rng, &bounds);
+            let new_radius = rand_init_radius(&mut rng);
             data.get_mut::<Vec<Vec2>>("positions")?[i] = new_pos;
             data.get_mut::<Vec<f32>>("radii")?[i] = new_radius;
             continue;
@@ -70,19 +95,17 @@ fn compute(sled: &Sled<Rgb>, data: &mut Data, time_info: &Time) -> SledResult {
         let new_radius = radius + delta * radius.max(1.0).sqrt().recip();
         data.get_mut::<Vec<f32>>("radii")?[i] = new_radius;
     }
-    Ok(())
+    rng::store(data, rng)
 }
 
-fn rand_point_in_range(range: &Range<Vec2>) -> Vec2 {
-    let mut rng = rand::thread_rng();
+fn rand_point_in_range(rng: &mut impl Rng, range: &Range<Vec2>) -> Vec2 {
     Vec2::new(
         rng.gen_range(range.start.x * 1.25..range.end.x * 1.25),
         rng.gen_range(range.start.y * 1.25..range.end.y * 1.25),
     )
 }
 
-fn rand_init_radius() -> f32 {
-    let mut rng = rand::thread_rng();
+fn rand_init_radius(rng: &mut impl Rng) -> f32 {
     // using a negative radius, we can scheudle a delay before the ripple actually appears
     rng.gen_range(-32.0..0.0)
 }
@@ -92,28 +115,25 @@ fn draw(sled: &mut Sled<Rgb>, data: &Data, _time: &Time) -> SledResult {
     let colors: &Vec<Rgb> = data.get("colors")?;
     let positions: &Vec<Vec2> = data.get("positions")?;
     let radii: &Vec<f32> = data.get("radii")?;
-    for i in 0..MAX_RIPPLES {
-        let pos = positions[i];
-        let radius = radii[i];
-
-        if radius > -FEATHERING {
-            draw_ripple_at(sled, pos, radius, colors[i % colors.len()]);
+    let feathering = *data.get::<f32>("feathering")?;
+    for (i, (&pos, &radius)) in positions.iter().zip(radii).enumerate() {
+        if radius > -feathering {
+            draw_ripple_at(sled, pos, radius, feathering, colors[i % colors.len()]);
         }
     }
-
-    // reinhard tonemapping
-    // sled.map(|led| led.color / (Rgb::new(1.0, 1.0, 1.0) + led.color));
     Ok(())
 }
 
-fn draw_ripple_at(sled: &mut Sled<Rgb>, pos: Vec2, radius: f32, color: Rgb) {
-    let inv_radius = 1.0 / radius;
-    sled.modulate_within_dist_from(radius + FEATHERING, pos, |led| {
+fn draw_ripple_at(sled: &mut Sled<Rgb>, pos: Vec2, radius: f32, feathering: f32, color: Rgb) {
+    // ripples fading in have a tiny or negative radius, don't let them blow up
+    let inv_radius = 1.0 / radius.max(feathering);
+    let inv_f = 1.0 / feathering;
+    sled.modulate_within_dist_from(radius + feathering, pos, |led| {
         let r = led.position().distance(pos);
         if r >= radius {
             let dist = r - radius;
-            if dist < FEATHERING {
-                let factor = (FEATHERING - dist) * INV_F;
+            if dist < feathering {
+                let factor = (feathering - dist) * inv_f;
                 return led.color + color * (factor * inv_radius);
             }
         } else {
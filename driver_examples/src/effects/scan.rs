@@ -0,0 +1,114 @@
+// `adapt_into` is deprecated, but keeps the colors exactly as the original example had them
+#![allow(deprecated)]
+
+use rand::Rng;
+use std::f32::consts::{PI, TAU};
+use std::time::Duration;
+
+use palette::{chromatic_adaptation::AdaptInto, oklch::Oklch, rgb::Rgb};
+
+use crate::{
+    clock::Driver,
+    decay,
+    params::{self, ParamSpec},
+    rng,
+};
+use spatial_led::{
+    driver::{Data, Time},
+    Sled, SledResult, Vec2,
+};
+
+const SCAN_DURATION: f32 = 4.0;
+const TRAIL_HALF_LIFE: f32 = 0.35;
+
+pub const PARAMS: &[ParamSpec] = &[
+    ParamSpec::float("scan_duration", SCAN_DURATION, 0.1..=60.0),
+    ParamSpec::float("trail_half_life", TRAIL_HALF_LIFE, 0.01..=5.0),
+];
+
+pub fn build_driver() -> Driver<Rgb> {
+    let mut driver = Driver::new();
+    driver.set_startup_commands(startup);
+    driver.set_compute_commands(compute);
+    driver.set_draw_commands(draw);
+
+    driver
+}
+
+fn rand_endpoints(rng: &mut impl Rng, sled: &Sled<Rgb>) -> (Vec2, Vec2) {
+    let domain = sled.domain();
+    let r = (domain.end - domain.start).length() * 0.6;
+    let c = sled.center_point();
+
+    let start_angle = rng.gen_range(0.0..TAU);
+    let end_angle = start_angle + PI;
+
+    let start = c + (Vec2::from_angle(start_angle) * r);
+    let end = c + (Vec2::from_angle(end_angle) * r);
+    (start, end)
+}
+
+fn start_new_scan(sled: &Sled<Rgb>, buffers: &mut Data, now: Duration) -> SledResult {
+    let duration = *buffers.get::<f32>("scan_duration")?;
+    let mut rng = rng::take(buffers)?;
+    let (start, end) = rand_endpoints(&mut rng, sled);
+    rng::store(buffers, rng)?;
+
+    let t_buffer = buffers.store::<Vec<Duration>>("times", vec![]);
+
+    t_buffer.push(now);
+    t_buffer.push(now + Duration::from_secs_f32(duration));
+
+    let endpoints = buffers.store::<Vec<Vec2>>("vectors", vec![]);
+    endpoints.push(start); // v0 will be start point
+    endpoints.push(end); // v1 will be end point
+    endpoints.push(start); // v2 will be interpolation between v1 and v2
+    endpoints.push((end - start).normalize()); // v3 will be direction of movement
+    Ok(())
+}
+
+fn startup(sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
+    params::store_defaults(data, PARAMS);
+    rng::init(data)?;
+    start_new_scan(sled, data, Duration::from_secs(0))
+}
+
+fn compute(sled: &Sled<Rgb>, data: &mut Data, time: &Time) -> SledResult {
+    let t_buffer = data.get::<Vec<Duration>>("times")?;
+    let now = time.elapsed;
+    let start_t = t_buffer[0];
+    let end_t = t_buffer[1];
+
+    if now > end_t {
+        return start_new_scan(sled, data, time.elapsed);
+    }
+
+    let v_buffer = data.get_mut::<Vec<Vec2>>("vectors")?;
+    let start_p = v_buffer[0];
+    let end_p = v_buffer[1];
+    let duration = (end_t - start_t).as_secs_f32();
+    let a = 1.0 - ((end_t.as_secs_f32() - now.as_secs_f32()) / duration);
+
+    v_buffer[2] = start_p.lerp(end_p, a);
+    Ok(())
+}
+
+fn draw(sled: &mut Sled<Rgb>, data: &Data, time: &Time) -> SledResult {
+    // gradual fade to black
+    let theta = ((time.elapsed.as_secs_f32() / 12.5).cos() + 1.0) * 180.0;
+    decay::fade(sled, time.delta, *data.get::<f32>("trail_half_life")?);
+
+    let v_buffer = data.get::<Vec<Vec2>>("vectors")?;
+    let scan_center = v_buffer[2];
+    let scan_direction = v_buffer[3];
+
+    let c: Rgb = Oklch::new(0.99, 0.3, theta).adapt_into();
+    // much of this hue circle is outside sRGB, where some channels go negative; drop
+    // those, but leave channels above 1 for [post] to tonemap
+    let c = Rgb::new(c.red.max(0.0), c.green.max(0.0), c.blue.max(0.0));
+
+    sled.set_at_dir_from(scan_direction.perp(), scan_center, c);
+    sled.set_at_dir_from(-scan_direction.perp(), scan_center, c);
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use rand::Rng;
+
+use palette::rgb::Rgb;
+
+use crate::{
+    clock::Driver,
+    decay,
+    params::{self, ParamSpec},
+    rng,
+};
+use spatial_led::{
+    driver::{Data, Time},
+    Sled, SledResult, Vec2,
+};
+
+const NUM_STARS: usize = 5000;
+const VELOCITY: f32 = 6.0;
+const TRAIL_HALF_LIFE: f32 = 0.028;
+#[allow(clippy::approx_constant)]
+const DIRECTION: Vec2 = Vec2::new(0.7071, -0.7071);
+
+// keeps stars sitting right on top of an led from blowing up to infinite brightness
+const MIN_DIST_SQ: f32 = 1e-3;
+
+pub const PARAMS: &[ParamSpec] = &[
+    ParamSpec::count("num_stars", NUM_STARS, 0..=20_000),
+    ParamSpec::float("velocity", VELOCITY, 0.0..=50.0),
+    ParamSpec::vec2("direction", DIRECTION, -1.0..=1.0),
+    ParamSpec::float("trail_half_life", TRAIL_HALF_LIFE, 0.001..=1.0),
+];
+
+pub fn build_driver() -> Driver<Rgb> {
+    let mut driver = Driver::new();
+
+    driver.set_startup_commands(startup);
+    driver.set_compute_commands(compute);
+    driver.set_draw_commands(draw);
+
+    driver
+}
+
+fn startup(sled: &mut Sled<Rgb>, data: &mut Data) -> SledResult {
+    params::store_defaults(data, PARAMS);
+    let num_stars = *data.get::<usize>("num_stars")?;
+    let direction = data.get::<Vec2>("direction")?.normalize_or_zero();
+    let mut rng = rng::init(data)?;
+
+    let stars = data.store::<Vec<Vec2>>("stars", vec![]);
+    let center = sled.center_point();
+
+    let orth = direction.perp();
+
+    for _ in 0..num_stars {
+        let sign = match rng.gen_bool(0.5) {
+            true => 1.0,
+            false => -1.0,
+        };
+
+        let spawn_pos = center
+            + (direction * rng.gen_range(40.0..300.0))
+            + (orth * rng.gen_range(1.45..35.0) * sign);
+
+        stars.push(spawn_pos);
+    }
+    rng::store(data, rng)?;
+
+    data.set::<Vec<Rgb>>(
+        "colors",
+        vec![
+            Rgb::new(0.15, 0.5, 1.0),
+            Rgb::new(0.25, 0.3, 1.0),
+            Rgb::new(0.05, 0.4, 0.8),
+            Rgb::new(0.7, 0.0, 0.6),
+            Rgb::new(0.05, 0.75, 1.0),
+            Rgb::new(0.1, 0.8, 0.6),
+            Rgb::new(0.6, 0.05, 0.2),
+            Rgb::new(0.85, 0.15, 0.3),
+            Rgb::new(0.0, 0.0, 1.0),
+            Rgb::new(1.0, 0.71, 0.705),
+        ],
+    );
+
+    Ok(())
+}
+
+fn compute(sled: &Sled<Rgb>, data: &mut Data, time: &Time) -> SledResult {
+    let mut rng = rng::take(data)?;
+    let delta = time.delta.as_secs_f32();
+    let num_stars = *data.get::<usize>("num_stars")?;
+    let velocity = *data.get::<f32>("velocity")?;
+    let direction = data.get::<Vec2>("direction")?.normalize_or_zero();
+
+    let stars = data.get_mut::<Vec<Vec2>>("stars")?;
+    let center = sled.center_point();
+
+    for star in stars.iter_mut() {
+        *star -= direction * velocity * delta;
+        if star.x.signum() != direction.x.signum() && star.y.signum() != direction.y.signum() {
+            let dq = (*star - center).length_squared();
+            if dq > 1000.0 {
+                *star = spawn_star(&mut rng, center, direction);
+            }
+        }
+    }
+
+    // follow along if the number of stars was changed at runtime
+    stars.truncate(num_stars);
+    while stars.len() < num_stars {
+        stars.push(spawn_star(&mut rng, center, direction));
+    }
+
+    rng::store(data, rng)
+}
+
+fn spawn_star(rng: &mut impl Rng, center: Vec2, direction: Vec2) -> Vec2 {
+    let sign = match rng.gen_bool(0.5) {
+        true => 1.0,
+        false => -1.0,
+    };
+
+    center
+        + (direction * rng.gen_range(40.0..300.0))
+        + (direction.perp() * rng.gen_range(1.5..35.0) * sign)
+}
+
+fn draw(sled: &mut Sled<Rgb>, data: &Data, time: &Time) -> SledResult {
+    let stars = data.get::<Vec<Vec2>>("stars")?;
+    let center = sled.center_point();
+
+    decay::fade(sled, time.delta, *data.get::<f32>("trail_half_life")?);
+
+    for (i, star) in stars.iter().enumerate() {
+        let d = Vec2::new(star.x - center.x, star.y - center.y);
+        let c = data.get::<Vec<Rgb>>("colors")?[i % 10];
+        sled.modulate_at_dir(d, |led| {
+            let d_sq = (d.length() - led.distance()).powi(2);
+            led.color + (c / d_sq.max(MIN_DIST_SQ))
+        });
+    }
+
+    Ok(())
+}
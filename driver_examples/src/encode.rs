@@ -0,0 +1,149 @@
+use palette::rgb::Rgb;
+use serde::Deserialize;
+use spatial_led::SledError;
+use toml::{Table, Value};
+
+use crate::{params::FromTable, strip};
+
+const LUT_SIZE: usize = 4096;
+
+/// Either one gamma for every channel, or one each for red, green and blue.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Gamma {
+    Uniform(f32),
+    PerChannel([f32; 3]),
+}
+
+impl Gamma {
+    pub fn channels(self) -> [f32; 3] {
+        match self {
+            Gamma::Uniform(gamma) => [gamma; 3],
+            Gamma::PerChannel(gammas) => gammas,
+        }
+    }
+}
+
+/// How frames are turned into the 8-bit values sent to hardware.
+///
+/// Loaded from the `[output]` table of a params file:
+///
+/// ```toml
+/// [output]
+/// gamma = 2.2               # or [2.2, 2.0, 2.4] for red, green and blue
+/// dither = true
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EncoderConfig {
+    pub gamma: Gamma,
+    /// Carries each LED's rounding error over to the next frame, so values between two
+    /// 8-bit steps are shown by alternating between them.
+    pub dither: bool,
+}
+
+impl Default for EncoderConfig {
+    fn default() -> Self {
+        EncoderConfig {
+            gamma: Gamma::Uniform(1.0),
+            dither: true,
+        }
+    }
+}
+
+impl FromTable for EncoderConfig {
+    fn from_table(table: &Table) -> Result<Self, SledError> {
+        let config: EncoderConfig = Value::Table(table.clone())
+            .try_into()
+            .map_err(|e| SledError::new(format!("Invalid [output] table: {}", e)))?;
+
+        if let Some(gamma) = config
+            .gamma
+            .channels()
+            .into_iter()
+            .find(|g| !(*g > 0.0 && g.is_finite()))
+        {
+            return SledError::new(format!("[output] can't use a gamma of {}.", gamma)).as_err();
+        }
+        Ok(config)
+    }
+}
+
+/// Gamma corrects and dithers frames into 8-bit RGBW, for every hardware output to share.
+/// White stays 0 unless the encoder is given the color of a white LED to extract.
+///
+/// Dithering keeps state per LED, so use one encoder per strip and feed it every frame.
+#[derive(Clone, Debug)]
+pub struct Encoder {
+    luts: [Vec<f32>; 4],
+    dither: bool,
+    white: Option<Rgb>,
+    error: Vec<[f32; 4]>,
+    output: Vec<[u8; 4]>,
+}
+
+impl Encoder {
+    pub fn new(config: &EncoderConfig) -> Self {
+        let lut = |gamma: f32| {
+            (0..LUT_SIZE)
+                .map(|i| (i as f32 / (LUT_SIZE - 1) as f32).powf(gamma) * 255.0)
+                .collect()
+        };
+        let [r, g, b] = config.gamma.channels();
+        // the white LED mixes all three, so it gets their average response
+        let w = (r + g + b) / 3.0;
+
+        Encoder {
+            luts: [lut(r), lut(g), lut(b), lut(w)],
+            dither: config.dither,
+            white: None,
+            error: vec![],
+            output: vec![],
+        }
+    }
+
+    /// Drives a white LED of color `white` with the part of each color it can show,
+    /// see [`strip::extract_white`].
+    pub fn with_white(mut self, white: Option<Rgb>) -> Self {
+        self.white = white;
+        self
+    }
+
+    /// The gamma corrected value of a channel, from 0 to 255 but not yet rounded.
+    /// Channels are red, green, blue and white.
+    pub fn correct(&self, channel: usize, value: f32) -> f32 {
+        // NaN casts to 0 along with anything negative
+        let index = (value.clamp(0.0, 1.0) * (LUT_SIZE - 1) as f32).round() as usize;
+        self.luts[channel][index]
+    }
+
+    pub fn encode<'a>(&mut self, colors: impl Iterator<Item = &'a Rgb>) -> &[[u8; 4]] {
+        self.output.clear();
+        for (i, color) in colors.enumerate() {
+            if self.error.len() <= i {
+                self.error.push([0.0; 4]);
+            }
+
+            let (color, white) = match self.white {
+                Some(white) => strip::extract_white(*color, white),
+                None => (*color, 0.0),
+            };
+            let channels = [color.red, color.green, color.blue, white];
+            let mut encoded = [0; 4];
+            for c in 0..4 {
+                let value = self.correct(c, channels[c]);
+                encoded[c] = match self.dither {
+                    true => {
+                        let wanted = value + self.error[i][c];
+                        let shown = wanted.round().clamp(0.0, 255.0);
+                        self.error[i][c] = wanted - shown;
+                        shown as u8
+                    }
+                    false => value.round() as u8,
+                };
+            }
+            self.output.push(encoded);
+        }
+        &self.output
+    }
+}
@@ -1 +1,23 @@
+pub mod clock;
+pub mod compositor;
+pub mod decay;
+pub mod effects;
+pub mod encode;
+pub mod net;
+pub mod params;
+pub mod playlist;
+pub mod post;
+pub mod power;
+pub mod registry;
+pub mod rng;
+pub mod sanitize;
+pub mod sink;
+pub mod strip;
+pub mod transition;
+pub mod wiring;
 
+#[cfg(feature = "tui")]
+pub mod tui;
+
+#[cfg(feature = "adalight")]
+pub mod adalight;
@@ -0,0 +1,649 @@
+use std::{
+    error::Error,
+    ops::Range,
+    path::Path,
+    process::ExitCode,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use clap::{Args, Parser, Subcommand};
+use palette::rgb::Rgb;
+use spatial_led::{driver::Data, scheduler::Scheduler, Sled, SledError, Vec2};
+
+#[cfg(feature = "adalight")]
+use driver_examples::adalight::{self, AdalightSink};
+use driver_examples::{
+    clock::{self, Driver},
+    compositor::{Compositor, LayerBlend},
+    net::{
+        dmx::{DmxProtocol, DmxSink},
+        input::NetworkInput,
+        opc::{OpcServer, OpcSink},
+        realtime::{RealtimeProtocol, RealtimeSink},
+    },
+    params::ParamFile,
+    playlist::Playlist,
+    post::{PostProcess, Tonemap},
+    power::PowerLimiter,
+    registry::{self, EffectInfo},
+    rng,
+    sanitize::{SanitizeMode, Sanitizer},
+    sink::{FanOut, Frame, LedSink, Recorder},
+    transition::{Blend, Crossfader},
+    tui::SledTerminalDisplay,
+};
+
+const DEFAULT_PARAMS_PATH: &str = "./params.toml";
+
+/// Preview spatial_led effects against your own room layouts.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List every effect that can be run.
+    List,
+    /// Run an effect, rendering it to the terminal. Number keys switch to the
+    /// effect listed under that number.
+    Run(RunArgs),
+    /// Cycle through the effects listed in a playlist file.
+    Play(PlayArgs),
+    /// Act as an Open Pixel Control server, showing the pixels clients send on the
+    /// room's layout.
+    Opc(OpcArgs),
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Name of the effect to run, as shown by `list`.
+    effect: String,
+    /// Seconds spent crossfading when switching effects with the number keys.
+    #[arg(long, default_value = "1.0", value_parser = seconds)]
+    transition: Duration,
+    /// How effects are mixed while crossfading: linear, oklab or dip-to-black.
+    #[arg(long, default_value_t = Blend::Linear)]
+    blend: Blend,
+    /// Stack another effect on top, e.g. `--layer ripples:screen:0.8`. Blends are add,
+    /// screen, multiply, max or alpha. Can be given more than once.
+    #[arg(long = "layer", value_name = "EFFECT[:BLEND[:OPACITY]]")]
+    layers: Vec<LayerArg>,
+    #[command(flatten)]
+    view: ViewArgs,
+}
+
+/// An effect composited over the one being run.
+#[derive(Clone)]
+struct LayerArg {
+    effect: &'static EffectInfo,
+    blend: LayerBlend,
+    opacity: f32,
+}
+
+impl FromStr for LayerArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        let effect = registry::find(name).ok_or_else(|| format!("Unknown effect `{}`.", name))?;
+        let blend = match parts.next() {
+            Some(blend) => blend.parse()?,
+            None => LayerBlend::Screen,
+        };
+        let opacity = match parts.next() {
+            Some(opacity) => opacity
+                .parse::<f32>()
+                .ok()
+                .filter(|o| (0.0..=1.0).contains(o))
+                .ok_or_else(|| format!("Opacity `{}` should be between 0 and 1.", opacity))?,
+            None => 1.0,
+        };
+        if parts.next().is_some() {
+            return Err(format!("Expected EFFECT[:BLEND[:OPACITY]], found `{}`.", s));
+        }
+
+        Ok(LayerArg {
+            effect,
+            blend,
+            opacity,
+        })
+    }
+}
+
+/// Parses a non-negative number of seconds.
+fn seconds(s: &str) -> Result<Duration, String> {
+    s.parse::<f32>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f32(secs).ok())
+        .ok_or_else(|| format!("Expected a non-negative number of seconds, found `{}`.", s))
+}
+
+#[derive(Args)]
+struct OpcArgs {
+    /// Room layout to show pixels on, mapped to its LEDs by index.
+    #[arg(long, default_value = "./complex_room.yap")]
+    room: String,
+    /// Address to accept clients on.
+    #[arg(long, default_value = "0.0.0.0:7890")]
+    listen: String,
+    /// Only show this channel, along with broadcasts. 0 shows every channel.
+    #[arg(long, default_value_t = 0)]
+    channel: u8,
+    /// How often the terminal is redrawn.
+    #[arg(long, default_value_t = 60.0)]
+    fps: f32,
+}
+
+#[derive(Args)]
+struct PlayArgs {
+    /// Playlist file listing effects, durations and presets.
+    playlist: String,
+    #[command(flatten)]
+    view: ViewArgs,
+}
+
+#[derive(Args)]
+struct ViewArgs {
+    /// Room layout to mount the effect on.
+    #[arg(long, default_value = "./complex_room.yap")]
+    room: String,
+    /// TOML file of parameter overrides, with one table per effect. Defaults to
+    /// `./params.toml` if it exists; pressing `s` in the TUI saves to this file.
+    #[arg(long)]
+    params: Option<String>,
+    /// Target frame rate.
+    #[arg(long, default_value_t = 500.0)]
+    fps: f32,
+    /// Step the effect without drawing it, printing frame timings instead.
+    #[arg(long)]
+    headless: bool,
+    /// Stop after this many seconds.
+    #[arg(long, value_parser = seconds)]
+    duration: Option<Duration>,
+    /// Seed every effect's randomness, overriding any seed in the params file.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Advance effects by exactly this many seconds each frame instead of following the
+    /// real clock. Together with `--seed`, every run renders identical frames.
+    #[arg(long, value_parser = seconds)]
+    timestep: Option<Duration>,
+    /// What to do with NaN, infinite or negative colors: clamp them, replace the LED
+    /// with black, or stop with an error naming the LED.
+    #[arg(long, default_value_t = SanitizeMode::Clamp)]
+    sanitize: SanitizeMode,
+    /// Tonemap applied to each frame (none, reinhard or aces), overriding the params
+    /// file's `[post]` table.
+    #[arg(long)]
+    tonemap: Option<Tonemap>,
+    /// Scale every frame's brightness, overriding the params file's `[post]` table.
+    #[arg(long)]
+    brightness: Option<f32>,
+    /// Dim frames that would draw more than this many milliamps, overriding the params
+    /// file's `[power]` budget.
+    #[arg(long)]
+    power_budget: Option<f32>,
+    /// Also write every frame to this file, one line of hex colors per frame.
+    #[arg(long, value_name = "FILE")]
+    record: Option<String>,
+    /// Also send every frame as Art-Net to this host, or broadcast it if no host is
+    /// given. Universes follow the params file's `[dmx]` table.
+    #[arg(long, value_name = "HOST", num_args = 0..=1)]
+    artnet: Option<Option<String>>,
+    /// Also send every frame as sACN (E1.31) to this host, or multicast it if no host
+    /// is given. Universes follow the params file's `[dmx]` table.
+    #[arg(long, value_name = "HOST", num_args = 0..=1)]
+    sacn: Option<Option<String>>,
+    /// Also stream every frame to this host over DDP, following the params file's
+    /// `[realtime]` table.
+    #[arg(long, value_name = "HOST")]
+    ddp: Option<String>,
+    /// Also stream every frame to this WLED device with its realtime UDP protocol,
+    /// following the params file's `[realtime]` table.
+    #[arg(long, value_name = "HOST")]
+    wled: Option<String>,
+    /// Also send every frame to this Open Pixel Control server, such as a Fadecandy's.
+    #[arg(long, value_name = "HOST")]
+    opc: Option<String>,
+    /// The OPC channel frames are sent on; 0 reaches every channel.
+    #[arg(long, default_value_t = 0)]
+    opc_channel: u8,
+    /// Also send every frame to a microcontroller running Adalight firmware on this
+    /// serial port, such as /dev/ttyUSB0 or COM3.
+    #[cfg(feature = "adalight")]
+    #[arg(long, value_name = "PORT")]
+    adalight: Option<String>,
+    /// The baud rate of the Adalight serial port, which must match the firmware's.
+    #[cfg(feature = "adalight")]
+    #[arg(long, default_value_t = adalight::DEFAULT_BAUD)]
+    baud: u32,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::List => {
+            list();
+            Ok(())
+        }
+        Command::Run(args) => run(args),
+        Command::Play(args) => play(args),
+        Command::Opc(args) => serve_opc(args),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn list() {
+    for (i, effect) in registry::EFFECTS.iter().enumerate() {
+        println!("{} {:<12}{}", i + 1, effect.name, effect.description);
+        if let Some(credit) = effect.credit {
+            println!("{:<14}Credit: {}", "", credit);
+        }
+        for spec in effect.params {
+            println!(
+                "{:<14}  {} = {} ({}, {:?})",
+                "",
+                spec.name,
+                spec.default,
+                spec.default.type_name(),
+                spec.range
+            );
+        }
+    }
+}
+
+fn run(args: RunArgs) -> Result<(), String> {
+    let effect = registry::find(&args.effect).ok_or_else(|| {
+        let names: Vec<_> = registry::names().collect();
+        format!(
+            "Unknown effect `{}`. Available: {}",
+            args.effect,
+            names.join(", ")
+        )
+    })?;
+
+    let sled = load_room(&args.view)?;
+    let (params, params_path) = load_params(&args.view)?;
+    let timestep = start_clock(&args.view);
+    let post = post_process(&args.view, &params);
+    let power = power_limiter(&args.view, &params, &sled)?;
+    let outputs = outputs(&args.view, &params)?;
+
+    let driver = build_layered(effect, &args.layers, &params).map_err(|e| e.to_string())?;
+    let domain = sled.domain();
+    let fader = Crossfader::new(sled, driver, args.blend, args.transition);
+    let positions = fader.positions().collect();
+
+    let session = Session {
+        fader,
+        positions,
+        effect,
+        layers: args.layers,
+        playlist: None,
+        params,
+        params_path,
+        timestep,
+        sanitizer: Sanitizer::new(args.view.sanitize),
+        post,
+        power,
+        outputs,
+    };
+    present(session, domain, &args.view)
+}
+
+fn play(args: PlayArgs) -> Result<(), String> {
+    let mut playlist = Playlist::load(&args.playlist).map_err(|e| e.to_string())?;
+    let sled = load_room(&args.view)?;
+    let (params, params_path) = load_params(&args.view)?;
+    let timestep = start_clock(&args.view);
+    let post = post_process(&args.view, &params);
+    let power = power_limiter(&args.view, &params, &sled)?;
+    let outputs = outputs(&args.view, &params)?;
+    if let Some(seed) = params.seed() {
+        playlist.reseed(seed);
+    }
+
+    let domain = sled.domain();
+    let fader = playlist.start(sled, &params).map_err(|e| e.to_string())?;
+    let positions = fader.positions().collect();
+
+    let session = Session {
+        fader,
+        positions,
+        effect: playlist.current().effect,
+        layers: vec![],
+        playlist: Some(playlist),
+        params,
+        params_path,
+        timestep,
+        sanitizer: Sanitizer::new(args.view.sanitize),
+        post,
+        power,
+        outputs,
+    };
+    present(session, domain, &args.view)
+}
+
+fn serve_opc(args: OpcArgs) -> Result<(), String> {
+    let sled: Sled<Rgb> =
+        Sled::new(&args.room).map_err(|e| format!("Failed to load room `{}`: {}", args.room, e))?;
+    let positions: Vec<Vec2> = sled.positions().collect();
+    let mut colors = vec![Rgb::new(0.0, 0.0, 0.0); positions.len()];
+    let mut server = OpcServer::bind(&args.listen, positions.len(), &Default::default())
+        .map_err(|e| format!("Failed to listen on `{}`: {}", args.listen, e))?
+        .with_channel(args.channel);
+
+    let title = |clients: usize| format!("OPC on {} ({} clients)", args.listen, clients);
+    let mut clients = 0;
+    let mut display = SledTerminalDisplay::start(&title(clients), sled.domain());
+    let mut scheduler = Scheduler::new(args.fps);
+    let error = scheduler.loop_until_err(|| {
+        server.poll()?;
+        if server.client_count() != clients {
+            clients = server.client_count();
+            display.set_title(title(clients));
+        }
+        server.apply(&mut colors);
+        display.send(&Frame::new(&colors, &positions))?;
+        Ok(())
+    });
+    finish(&mut display, error)
+}
+
+fn load_room(view: &ViewArgs) -> Result<Sled<Rgb>, String> {
+    Sled::new(&view.room).map_err(|e| format!("Failed to load room `{}`: {}", view.room, e))
+}
+
+fn load_params(view: &ViewArgs) -> Result<(ParamFile, String), String> {
+    // an explicit --params file must exist, the default one is optional
+    let path = view.params.as_deref().unwrap_or(DEFAULT_PARAMS_PATH);
+    let mut params = match view.params.is_some() || Path::new(path).exists() {
+        true => ParamFile::load(path).map_err(|e| e.to_string())?,
+        false => ParamFile::default(),
+    };
+    if let Some(seed) = view.seed {
+        params.set_seed(seed);
+    }
+    Ok((params, path.to_string()))
+}
+
+fn post_process(view: &ViewArgs, params: &ParamFile) -> PostProcess {
+    let mut post = params.post();
+    if let Some(tonemap) = view.tonemap {
+        post.tonemap = tonemap;
+    }
+    if let Some(brightness) = view.brightness {
+        post.brightness = brightness.max(0.0);
+    }
+    post
+}
+
+fn power_limiter(
+    view: &ViewArgs,
+    params: &ParamFile,
+    sled: &Sled<Rgb>,
+) -> Result<PowerLimiter, String> {
+    let mut config = params.power();
+    if let Some(budget) = view.power_budget {
+        config.budget_ma = Some(budget.max(0.0));
+    }
+    PowerLimiter::new(config, sled).map_err(|e| e.to_string())
+}
+
+/// Every place frames go besides the terminal.
+fn outputs(view: &ViewArgs, params: &ParamFile) -> Result<FanOut, String> {
+    let mut outputs = FanOut::new();
+    if let Some(path) = &view.record {
+        outputs.push(Recorder::create(path).map_err(|e| e.to_string())?);
+    }
+    for (protocol, target) in [
+        (DmxProtocol::ArtNet, &view.artnet),
+        (DmxProtocol::Sacn, &view.sacn),
+    ] {
+        if let Some(target) = target {
+            let sink = DmxSink::new(protocol, target.as_deref(), params.dmx(), &params.output())
+                .map_err(|e| e.to_string())?;
+            outputs.push(sink);
+        }
+    }
+    for (protocol, target) in [
+        (RealtimeProtocol::Ddp, &view.ddp),
+        (RealtimeProtocol::Wled, &view.wled),
+    ] {
+        if let Some(target) = target {
+            let sink = RealtimeSink::new(protocol, target, &params.realtime(), &params.output())
+                .map_err(|e| e.to_string())?;
+            outputs.push(sink);
+        }
+    }
+    if let Some(target) = &view.opc {
+        let sink = OpcSink::connect(target, view.opc_channel, &params.output())
+            .map_err(|e| e.to_string())?;
+        outputs.push(sink);
+    }
+    #[cfg(feature = "adalight")]
+    if let Some(path) = &view.adalight {
+        let sink =
+            AdalightSink::open(path, view.baud, &params.output()).map_err(|e| e.to_string())?;
+        outputs.push(sink);
+    }
+    Ok(outputs)
+}
+
+/// Freezes the clock when a fixed timestep was asked for, returning the step to take
+/// each frame. This has to happen before any driver is mounted.
+fn start_clock(view: &ViewArgs) -> Option<Duration> {
+    let step = view.timestep?;
+    clock::freeze();
+    Some(step)
+}
+
+/// Builds `effect`, compositing any `layers` over it.
+fn build_layered(
+    effect: &EffectInfo,
+    layers: &[LayerArg],
+    params: &ParamFile,
+) -> Result<Driver<Rgb>, SledError> {
+    let driver = effect.build_driver_with(params)?;
+    if layers.is_empty() {
+        return Ok(driver);
+    }
+
+    let mut compositor = Compositor::new().with_layer(driver, LayerBlend::Alpha, 1.0);
+    for layer in layers {
+        let driver = layer.effect.build_driver_with(params)?;
+        compositor.push(driver, layer.blend, layer.opacity);
+    }
+
+    let mut driver = compositor.build_driver();
+    if let Some(seed) = params.seed() {
+        rng::set_seed(driver.data_mut(), seed);
+    }
+    Ok(driver)
+}
+
+/// The data of the effect being edited, which is the bottom layer of a composited driver.
+fn effect_data(driver: &mut Driver<Rgb>) -> Result<&mut Data, SledError> {
+    if driver.data().empty_at("layers") {
+        return Ok(driver.data_mut());
+    }
+    let compositor: &mut Compositor = driver.data_mut().get_mut("layers")?;
+    Ok(compositor.layers_mut()[0].driver_mut().data_mut())
+}
+
+/// Everything that changes while effects are playing.
+struct Session {
+    fader: Crossfader,
+    positions: Vec<Vec2>,
+    effect: &'static EffectInfo,
+    layers: Vec<LayerArg>,
+    playlist: Option<Playlist>,
+    params: ParamFile,
+    params_path: String,
+    timestep: Option<Duration>,
+    sanitizer: Sanitizer,
+    post: PostProcess,
+    power: PowerLimiter,
+    outputs: FanOut,
+}
+
+impl Session {
+    fn step(&mut self) -> Result<(), SledError> {
+        if let Some(step) = self.timestep {
+            clock::advance(step);
+        }
+        if let Some(playlist) = &mut self.playlist {
+            if let Some(entry) = playlist.update(&mut self.fader, &self.params)? {
+                self.effect = entry.effect;
+            }
+        }
+        self.fader.step();
+        self.sanitizer.apply(self.fader.colors_mut())?;
+        self.post.apply(self.fader.colors_mut());
+        self.power.apply(self.fader.colors_mut());
+        // borrows the fields separately, unlike `frame`
+        let frame = Frame::new(self.fader.output(), &self.positions);
+        self.outputs.send(&frame)
+    }
+
+    fn frame(&self) -> Frame<'_> {
+        Frame::new(self.fader.output(), &self.positions)
+    }
+}
+
+fn present(mut session: Session, domain: Range<Vec2>, view: &ViewArgs) -> Result<(), String> {
+    let deadline = view.duration.map(|duration| Instant::now() + duration);
+    let mut scheduler = Scheduler::new(view.fps);
+
+    if view.headless {
+        return run_headless(&mut session, &mut scheduler, deadline).map_err(|e| e.to_string());
+    }
+
+    let mut shown = session.effect;
+    let mut display = SledTerminalDisplay::start(&title(shown), domain);
+    display.show_params(shown.params);
+    let mut finished = false;
+    let error = scheduler.loop_until_err(|| {
+        if let Some(next) = display
+            .take_effect_request()
+            .and_then(|i| registry::EFFECTS.get(i))
+        {
+            let driver = build_layered(next, &session.layers, &session.params)?;
+            session.fader.switch_to(driver);
+            session.effect = next;
+        }
+
+        display.sync_params(effect_data(session.fader.target_mut())?)?;
+        if display.take_save_request() {
+            let Session {
+                fader,
+                effect,
+                params,
+                params_path,
+                ..
+            } = &mut session;
+            params.record(effect.name, effect.params, effect_data(fader.target_mut())?)?;
+            params.save(&*params_path)?;
+            display.set_title(format!("{} (saved to {})", title(effect), params_path));
+        }
+
+        session.step()?;
+        if !std::ptr::eq(shown, session.effect) {
+            shown = session.effect;
+            display.show_params(shown.params);
+            display.set_title(title(shown));
+        }
+
+        display.send(&session.frame())?;
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            finished = true;
+            return Err("Reached the requested duration.".into());
+        }
+        Ok(())
+    });
+    match finished {
+        true => display.stop().map_err(|e| e.to_string()),
+        false => finish(&mut display, error),
+    }
+}
+
+/// Gives the terminal back once a display loop has ended with `error`. Quitting with `q`
+/// ends the loop too, but isn't a failure.
+fn finish(display: &mut SledTerminalDisplay, error: Box<dyn Error>) -> Result<(), String> {
+    let stopped = display.stop().map_err(|e| e.to_string());
+    match display.has_quit() {
+        true => stopped,
+        false => Err(error.to_string()),
+    }
+}
+
+fn run_headless(
+    session: &mut Session,
+    scheduler: &mut Scheduler,
+    deadline: Option<Instant>,
+) -> Result<(), SledError> {
+    let mut frames = 0;
+    let mut busy = Duration::ZERO;
+    let mut last_report = Instant::now();
+    let mut result = Ok(());
+
+    // a playlist seeds each entry itself, so only a single effect's seed is worth showing.
+    // effects without any randomness (like comet) never pick one.
+    if let (None, Ok(seed)) = (
+        &session.playlist,
+        session.fader.target().data().get::<u64>(rng::SEED),
+    ) {
+        println!("{}: seed {}", session.effect.name, seed);
+    }
+
+    scheduler.loop_while_true(|| {
+        let start = Instant::now();
+        if let Err(e) = session.step() {
+            result = Err(e);
+            return true;
+        }
+        busy += start.elapsed();
+        frames += 1;
+
+        if last_report.elapsed() >= Duration::from_secs(1) {
+            let power = session.power.last_estimate();
+            let limited = match power.scale < 1.0 {
+                true => format!(" (limited from {:.0} mA)", power.requested_ma),
+                false => String::new(),
+            };
+            println!(
+                "{}: {} frames, {:.3} ms average step, {:.0} mA{}",
+                session.effect.name,
+                frames,
+                busy.as_secs_f64() * 1000.0 / frames as f64,
+                power.output_ma,
+                limited
+            );
+            frames = 0;
+            busy = Duration::ZERO;
+            last_report = Instant::now();
+        }
+
+        deadline.is_some_and(|d| Instant::now() >= d)
+    });
+    result
+}
+
+fn title(effect: &EffectInfo) -> String {
+    let mut chars = effect.name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,70 @@
+//! Art-Net 4 packets: `ArtDmx` for universe data and `ArtSync` to latch them together.
+
+pub const PORT: u16 = 6454;
+
+const ID: &[u8; 8] = b"Art-Net\0";
+const OP_DMX: u16 = 0x5000;
+const OP_SYNC: u16 = 0x5200;
+const PROTOCOL_VERSION: u16 = 14;
+
+fn header(op: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(18 + 512);
+    packet.extend_from_slice(ID);
+    packet.extend_from_slice(&op.to_le_bytes());
+    packet.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+    packet
+}
+
+/// An `ArtDmx` packet carrying up to 512 channels to the 15-bit port address `universe`.
+/// A `sequence` of 0 tells receivers not to reorder packets.
+pub fn dmx_packet(universe: u16, sequence: u8, data: &[u8]) -> Vec<u8> {
+    let data = &data[..data.len().min(512)];
+    // the length has to be even, and at least 2
+    let length = (data.len() + data.len() % 2).max(2);
+
+    let mut packet = header(OP_DMX);
+    packet.push(sequence);
+    packet.push(0); // physical input port
+    packet.extend_from_slice(&(universe & 0x7fff).to_le_bytes());
+    packet.extend_from_slice(&(length as u16).to_be_bytes());
+    packet.extend_from_slice(data);
+    packet.resize(18 + length, 0);
+    packet
+}
+
+/// An `ArtSync` packet, telling receivers to show the data they've been sent.
+pub fn sync_packet() -> Vec<u8> {
+    let mut packet = header(OP_SYNC);
+    packet.extend_from_slice(&[0, 0]); // aux
+    packet
+}
+
+/// An incoming Art-Net packet this crate understands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Packet<'a> {
+    Dmx {
+        universe: u16,
+        sequence: u8,
+        data: &'a [u8],
+    },
+    Sync,
+}
+
+/// Reads an `ArtDmx` or `ArtSync` packet, or `None` for anything else.
+pub fn parse(packet: &[u8]) -> Option<Packet<'_>> {
+    if packet.len() < 12 || packet[..8] != *ID {
+        return None;
+    }
+    match u16::from_le_bytes([packet[8], packet[9]]) {
+        OP_DMX if packet.len() >= 18 => {
+            let length = u16::from_be_bytes([packet[16], packet[17]]) as usize;
+            Some(Packet::Dmx {
+                universe: u16::from_le_bytes([packet[14], packet[15]]) & 0x7fff,
+                sequence: packet[12],
+                data: packet.get(18..18 + length.min(512))?,
+            })
+        }
+        OP_SYNC => Some(Packet::Sync),
+        _ => None,
+    }
+}
@@ -0,0 +1,89 @@
+//! Distributed Display Protocol packets, as spoken by WLED, xLights and many pixel
+//! controllers.
+
+pub const PORT: u16 = 4048;
+/// The most pixel data one packet carries: 480 RGB pixels.
+pub const MAX_DATA: usize = 1440;
+
+const HEADER_LENGTH: usize = 10;
+const VERSION_1: u8 = 0x40;
+const FLAG_TIMECODE: u8 = 0x10;
+const FLAG_PUSH: u8 = 0x01;
+// type RGB or RGBW in the top bits, 8 bits per channel in the bottom ones
+const TYPE_RGB8: u8 = 0x0b;
+const TYPE_RGBW8: u8 = 0x1b;
+const DESTINATION_DISPLAY: u8 = 1;
+// control, configuration and status ids, which don't carry pixels
+const RESERVED_DESTINATIONS: std::ops::RangeInclusive<u8> = 246..=254;
+
+/// A packet of 8-bit RGB, or RGBW if `white`, starting `offset` bytes into the
+/// display. `push` tells the receiver to show everything it's been sent.
+pub fn packet(sequence: u8, offset: usize, data: &[u8], white: bool, push: bool) -> Vec<u8> {
+    let data = &data[..data.len().min(MAX_DATA)];
+    let mut packet = Vec::with_capacity(HEADER_LENGTH + data.len());
+    packet.push(VERSION_1 | if push { FLAG_PUSH } else { 0 });
+    packet.push(sequence & 0x0f);
+    packet.push(if white { TYPE_RGBW8 } else { TYPE_RGB8 });
+    packet.push(DESTINATION_DISPLAY);
+    packet.extend_from_slice(&(offset as u32).to_be_bytes());
+    packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    packet.extend_from_slice(data);
+    packet
+}
+
+/// Splits a whole frame into packets, pushing with the last.
+pub fn frame_packets(sequence: u8, data: &[u8], white: bool) -> Vec<Vec<u8>> {
+    let pixel = if white { 4 } else { 3 };
+    // keep pixels whole within each packet
+    let chunk = MAX_DATA / pixel * pixel;
+    let count = data.len().div_ceil(chunk).max(1);
+    (0..count)
+        .map(|i| {
+            let start = (i * chunk).min(data.len());
+            let end = (start + chunk).min(data.len());
+            packet(sequence, start, &data[start..end], white, i == count - 1)
+        })
+        .collect()
+}
+
+/// An incoming DDP data packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Packet<'a> {
+    pub sequence: u8,
+    /// Where `data` starts in the display, in bytes.
+    pub offset: usize,
+    /// Whether pixels are RGBW rather than RGB.
+    pub white: bool,
+    pub push: bool,
+    pub data: &'a [u8],
+}
+
+/// Reads a DDP packet carrying 8-bit pixels, or `None` for anything else.
+pub fn parse(packet: &[u8]) -> Option<Packet<'_>> {
+    let flags = *packet.first()?;
+    if packet.len() < HEADER_LENGTH || flags & 0xc0 != VERSION_1 {
+        return None;
+    }
+    if RESERVED_DESTINATIONS.contains(&packet[3]) {
+        return None;
+    }
+    let white = match packet[2] {
+        // senders that leave the type undefined mean RGB
+        0x00 | 0x01 | TYPE_RGB8 => false,
+        TYPE_RGBW8 => true,
+        _ => return None,
+    };
+    let header = match flags & FLAG_TIMECODE {
+        0 => HEADER_LENGTH,
+        _ => HEADER_LENGTH + 4,
+    };
+    let offset = u32::from_be_bytes(packet[4..8].try_into().unwrap()) as usize;
+    let length = u16::from_be_bytes([packet[8], packet[9]]) as usize;
+    Some(Packet {
+        sequence: packet[1] & 0x0f,
+        offset,
+        white,
+        push: flags & FLAG_PUSH != 0,
+        data: packet.get(header..header + length)?,
+    })
+}
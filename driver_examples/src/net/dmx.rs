@@ -0,0 +1,323 @@
+use std::{
+    fmt,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    str::FromStr,
+};
+
+use serde::Deserialize;
+use spatial_led::{SledError, SledResult};
+use toml::{Table, Value};
+
+use super::{artnet, input::InputLayer, merge::MergeMode, resolve, sacn};
+use crate::{
+    encode::{Encoder, EncoderConfig},
+    params::FromTable,
+    sink::{Frame, LedSink},
+    strip::kelvin_to_rgb,
+};
+
+const DMX_CHANNELS: usize = 512;
+
+/// How universes are sent over the network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DmxProtocol {
+    ArtNet,
+    Sacn,
+}
+
+impl DmxProtocol {
+    pub fn port(self) -> u16 {
+        match self {
+            DmxProtocol::ArtNet => artnet::PORT,
+            DmxProtocol::Sacn => sacn::PORT,
+        }
+    }
+}
+
+impl FromStr for DmxProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "artnet" | "art-net" => Ok(DmxProtocol::ArtNet),
+            "sacn" | "e131" | "e1.31" => Ok(DmxProtocol::Sacn),
+            _ => Err(format!(
+                "Unknown protocol `{}`. Expected artnet or sacn.",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DmxProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DmxProtocol::ArtNet => write!(f, "artnet"),
+            DmxProtocol::Sacn => write!(f, "sacn"),
+        }
+    }
+}
+
+/// How LEDs are laid out across DMX universes.
+///
+/// Loaded from the `[dmx]` table of a params file:
+///
+/// ```toml
+/// [dmx]
+/// start_universe = 1
+/// channels_per_pixel = 3    # 4 for RGBW fixtures
+/// pixels_per_universe = 170 # the most that fit by default
+/// sync = true               # latch every universe at once with a sync packet
+/// priority = 100            # sACN only
+///
+/// # when receiving
+/// merge = "htp"             # htp or ltp, for sources at the same priority
+/// layer = "over"            # replace, over or htp, against the local driver
+/// source_timeout = 2.5      # seconds before a quiet source is dropped
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DmxConfig {
+    pub start_universe: u16,
+    pub channels_per_pixel: usize,
+    pub pixels_per_universe: Option<usize>,
+    pub sync: bool,
+    /// sACN only, the universe sync packets go to. Defaults to `start_universe`.
+    pub sync_universe: Option<u16>,
+    pub priority: u8,
+    pub source_name: String,
+    /// The color of the white channel, in kelvin, when sending four channels per pixel.
+    pub white_temperature: f32,
+    pub merge: MergeMode,
+    pub layer: InputLayer,
+    pub source_timeout: f32,
+}
+
+impl Default for DmxConfig {
+    fn default() -> Self {
+        DmxConfig {
+            start_universe: 1,
+            channels_per_pixel: 3,
+            pixels_per_universe: None,
+            sync: true,
+            sync_universe: None,
+            priority: 100,
+            source_name: "spatial_led".to_string(),
+            white_temperature: 4000.0,
+            merge: MergeMode::default(),
+            layer: InputLayer::default(),
+            source_timeout: 2.5,
+        }
+    }
+}
+
+impl FromTable for DmxConfig {
+    fn from_table(table: &Table) -> Result<Self, SledError> {
+        let config: DmxConfig = Value::Table(table.clone())
+            .try_into()
+            .map_err(|e| SledError::new(format!("Invalid [dmx] table: {}", e)))?;
+
+        if !matches!(config.channels_per_pixel, 3 | 4) {
+            return SledError::new(format!(
+                "[dmx] channels_per_pixel should be 3 or 4, found {}.",
+                config.channels_per_pixel
+            ))
+            .as_err();
+        }
+        let most = DMX_CHANNELS / config.channels_per_pixel;
+        if let Some(pixels) = config.pixels_per_universe {
+            if !(1..=most).contains(&pixels) {
+                return SledError::new(format!(
+                    "[dmx] pixels_per_universe should be between 1 and {}, found {}.",
+                    most, pixels
+                ))
+                .as_err();
+            }
+        }
+        if config.priority > 200 {
+            return SledError::new(format!(
+                "[dmx] priority should be at most 200, found {}.",
+                config.priority
+            ))
+            .as_err();
+        }
+        if !(1000.0..=40_000.0).contains(&config.white_temperature) {
+            return SledError::new(format!(
+                "[dmx] white_temperature should be between 1000 and 40000 kelvin, found {}.",
+                config.white_temperature
+            ))
+            .as_err();
+        }
+        if !(config.source_timeout > 0.0 && config.source_timeout.is_finite()) {
+            return SledError::new(format!(
+                "[dmx] source_timeout should be a positive number of seconds, found {}.",
+                config.source_timeout
+            ))
+            .as_err();
+        }
+        Ok(config)
+    }
+}
+
+impl DmxConfig {
+    /// How many pixels go in each universe, which is as many as fit unless configured.
+    pub fn pixels_per_universe(&self) -> usize {
+        self.pixels_per_universe
+            .unwrap_or(DMX_CHANNELS / self.channels_per_pixel)
+    }
+
+    /// How many universes `leds` pixels take up.
+    pub fn universe_count(&self, leds: usize) -> usize {
+        leds.div_ceil(self.pixels_per_universe())
+    }
+}
+
+/// Sends frames as Art-Net or sACN universes, followed by a sync packet so receivers
+/// show every universe of a frame at once.
+pub struct DmxSink {
+    protocol: DmxProtocol,
+    config: DmxConfig,
+    socket: UdpSocket,
+    // None broadcasts Art-Net, or multicasts each sACN universe
+    target: Option<SocketAddr>,
+    encoder: Encoder,
+    source: sacn::Source,
+    sequences: Vec<u8>,
+    sync_sequence: u8,
+    data: Vec<u8>,
+}
+
+impl DmxSink {
+    /// Sends to `target`, a host with an optional port, or to everyone listening if
+    /// there's no target.
+    pub fn new(
+        protocol: DmxProtocol,
+        target: Option<&str>,
+        config: DmxConfig,
+        output: &EncoderConfig,
+    ) -> Result<Self, SledError> {
+        let target = target
+            .map(|target| resolve(target, protocol.port()))
+            .transpose()?;
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(SledError::from_error)?;
+        if target.is_none() && protocol == DmxProtocol::ArtNet {
+            socket.set_broadcast(true).map_err(SledError::from_error)?;
+        }
+
+        let white = match config.channels_per_pixel {
+            4 => Some(kelvin_to_rgb(config.white_temperature)),
+            _ => None,
+        };
+        let source = sacn::Source {
+            cid: rand::random(),
+            name: config.source_name.clone(),
+            priority: config.priority,
+        };
+
+        Ok(DmxSink {
+            protocol,
+            config,
+            socket,
+            target,
+            encoder: Encoder::new(output).with_white(white),
+            source,
+            sequences: vec![],
+            sync_sequence: 0,
+            data: vec![],
+        })
+    }
+
+    pub fn config(&self) -> &DmxConfig {
+        &self.config
+    }
+
+    fn destination(&self, universe: u16) -> SocketAddr {
+        match (self.target, self.protocol) {
+            (Some(target), _) => target,
+            (None, DmxProtocol::ArtNet) => (Ipv4Addr::BROADCAST, artnet::PORT).into(),
+            (None, DmxProtocol::Sacn) => (
+                Ipv4Addr::from(sacn::multicast_address(universe)),
+                sacn::PORT,
+            )
+                .into(),
+        }
+    }
+
+    fn send_to(&self, packet: &[u8], universe: u16) -> SledResult {
+        self.socket
+            .send_to(packet, self.destination(universe))
+            .map(|_| ())
+            .map_err(SledError::from_error)
+    }
+
+    fn sync_universe(&self) -> u16 {
+        self.config
+            .sync_universe
+            .unwrap_or(self.config.start_universe)
+    }
+}
+
+impl LedSink for DmxSink {
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        let count = self.config.universe_count(frame.colors.len());
+        let last = self.config.start_universe as usize + count.saturating_sub(1);
+        let highest = match self.protocol {
+            DmxProtocol::ArtNet => 0x7fff,
+            DmxProtocol::Sacn => sacn::MAX_UNIVERSE as usize,
+        };
+        if last > highest || (self.protocol == DmxProtocol::Sacn && self.config.start_universe == 0)
+        {
+            return SledError::new(format!(
+                "{} LEDs need universes {} to {}, past what {} can address.",
+                frame.colors.len(),
+                self.config.start_universe,
+                last,
+                self.protocol
+            ))
+            .as_err();
+        }
+        self.sequences.resize(count, 0);
+
+        let channels = self.config.channels_per_pixel;
+        self.data.clear();
+        for pixel in self.encoder.encode(frame.colors.iter()) {
+            self.data.extend_from_slice(&pixel[..channels]);
+        }
+
+        let sync_universe = match self.config.sync {
+            true => self.sync_universe(),
+            false => 0,
+        };
+        let chunk = self.config.pixels_per_universe() * channels;
+        for (i, data) in self.data.chunks(chunk).enumerate() {
+            let universe = self.config.start_universe + i as u16;
+            let sequence = next_sequence(&mut self.sequences[i], self.protocol);
+            let packet = match self.protocol {
+                DmxProtocol::ArtNet => artnet::dmx_packet(universe, sequence, data),
+                DmxProtocol::Sacn => {
+                    sacn::data_packet(&self.source, universe, sequence, sync_universe, data)
+                }
+            };
+            self.send_to(&packet, universe)?;
+        }
+
+        if self.config.sync {
+            let sequence = next_sequence(&mut self.sync_sequence, self.protocol);
+            let packet = match self.protocol {
+                DmxProtocol::ArtNet => artnet::sync_packet(),
+                DmxProtocol::Sacn => sacn::sync_packet(&self.source.cid, sequence, sync_universe),
+            };
+            self.send_to(&packet, sync_universe)?;
+        }
+        Ok(())
+    }
+}
+
+// Art-Net reserves sequence 0 for "don't reorder", so it counts 1 to 255
+fn next_sequence(sequence: &mut u8, protocol: DmxProtocol) -> u8 {
+    *sequence = match (protocol, sequence.wrapping_add(1)) {
+        (DmxProtocol::ArtNet, 0) => 1,
+        (_, next) => next,
+    };
+    *sequence
+}
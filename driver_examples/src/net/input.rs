@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use palette::rgb::Rgb;
+use serde::Deserialize;
+use spatial_led::{SledError, SledResult};
+
+use super::{
+    artnet,
+    dmx::{DmxConfig, DmxProtocol},
+    merge::{Merger, SourceId},
+    sacn,
+};
+use crate::{encode::EncoderConfig, strip::kelvin_to_rgb};
+
+// Art-Net receivers go back to showing data as it arrives after this long without an ArtSync
+const ARTNET_SYNC_TIMEOUT: Duration = Duration::from_secs(4);
+// the priority Art-Net sources merge at, since the protocol has none; sACN's default
+const ARTNET_PRIORITY: u8 = 100;
+
+/// How received universes combine with the frame the local driver drew.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputLayer {
+    /// Only show what's received, with LEDs no source is sending left dark.
+    Replace,
+    /// Show what's received wherever a source is sending, and the driver elsewhere.
+    #[default]
+    Over,
+    /// Show the brighter of the driver and what's received, channel by channel.
+    Htp,
+}
+
+impl InputLayer {
+    /// Combines `received`, if anything was, into the driver's `color`.
+    pub fn blend(self, color: &mut Rgb, received: Option<Rgb>) {
+        *color = match (self, received) {
+            (InputLayer::Replace, None) => Rgb::new(0.0, 0.0, 0.0),
+            (_, None) => *color,
+            (InputLayer::Replace | InputLayer::Over, Some(received)) => received,
+            (InputLayer::Htp, Some(received)) => Rgb::new(
+                color.red.max(received.red),
+                color.green.max(received.green),
+                color.blue.max(received.blue),
+            ),
+        };
+    }
+}
+
+/// Pixels arriving over the network, to be layered over what the local driver draws.
+pub trait NetworkInput {
+    /// Reads every packet that has arrived since the last poll, returning how many
+    /// were understood.
+    fn poll(&mut self) -> Result<usize, SledError>;
+
+    /// Layers what's been received over `colors`, the driver's frame.
+    fn apply(&mut self, colors: &mut [Rgb]);
+}
+
+impl<I: NetworkInput + ?Sized> NetworkInput for Box<I> {
+    fn poll(&mut self) -> Result<usize, SledError> {
+        (**self).poll()
+    }
+
+    fn apply(&mut self, colors: &mut [Rgb]) {
+        (**self).apply(colors)
+    }
+}
+
+/// Turns received levels back into colors, through the inverse of the `[output]`
+/// gamma, so a frame that isn't otherwise changed reaches the strip with the bytes
+/// that were sent.
+#[derive(Clone, Debug)]
+pub(crate) struct Decoder {
+    levels: [[f32; 256]; 4],
+    white: Rgb,
+}
+
+impl Decoder {
+    pub(crate) fn new(output: &EncoderConfig, white: Rgb) -> Self {
+        let [r, g, b] = output.gamma.channels();
+        let table = |gamma: f32| std::array::from_fn(|i| (i as f32 / 255.0).powf(1.0 / gamma));
+        Decoder {
+            levels: [table(r), table(g), table(b), table((r + g + b) / 3.0)],
+            white,
+        }
+    }
+
+    /// The color of the pixel whose channels start at `at`, with any white mixed back
+    /// in. Channels past the end of `data` are dark.
+    pub(crate) fn decode(&self, data: &[u8], at: usize, channels: usize) -> Rgb {
+        let level = |c: usize| {
+            let byte = data.get(at + c).copied().unwrap_or(0);
+            self.levels[c][byte as usize]
+        };
+        let w = match channels {
+            4 => level(3),
+            _ => 0.0,
+        };
+        Rgb::new(
+            level(0) + self.white.red * w,
+            level(1) + self.white.green * w,
+            level(2) + self.white.blue * w,
+        )
+    }
+}
+
+/// Listens for Art-Net and sACN universes laid out as in `DmxConfig`, so lighting
+/// consoles and other software can drive the LEDs.
+pub struct DmxInput {
+    config: DmxConfig,
+    sockets: Vec<(DmxProtocol, UdpSocket)>,
+    merger: Merger,
+    // Art-Net sources that have sent an ArtSync lately, whose data waits for the next one
+    artnet_synced: HashMap<IpAddr, Instant>,
+    decoder: Decoder,
+}
+
+impl DmxInput {
+    pub fn new(config: DmxConfig, output: &EncoderConfig) -> Self {
+        DmxInput {
+            merger: Merger::new(config.merge, Duration::from_secs_f32(config.source_timeout)),
+            decoder: Decoder::new(output, kelvin_to_rgb(config.white_temperature)),
+            config,
+            sockets: vec![],
+            artnet_synced: HashMap::new(),
+        }
+    }
+
+    /// Listens for `protocol` on its usual port, joining the sACN multicast group of
+    /// every universe `leds` LEDs take up.
+    pub fn listen(&mut self, protocol: DmxProtocol, leds: usize) -> SledResult {
+        self.bind(protocol, (Ipv4Addr::UNSPECIFIED, protocol.port()))?;
+        if protocol == DmxProtocol::Sacn {
+            let socket = &self.sockets.last().unwrap().1;
+            for universe in self.universes(leds) {
+                let group = Ipv4Addr::from(sacn::multicast_address(universe));
+                socket
+                    .join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)
+                    .map_err(SledError::from_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Listens for `protocol` on `address`, returning the address bound to.
+    pub fn bind(
+        &mut self,
+        protocol: DmxProtocol,
+        address: impl ToSocketAddrs,
+    ) -> Result<SocketAddr, SledError> {
+        let socket = UdpSocket::bind(address).map_err(SledError::from_error)?;
+        socket
+            .set_nonblocking(true)
+            .map_err(SledError::from_error)?;
+        let address = socket.local_addr().map_err(SledError::from_error)?;
+        self.sockets.push((protocol, socket));
+        Ok(address)
+    }
+
+    pub fn config(&self) -> &DmxConfig {
+        &self.config
+    }
+
+    pub fn merger(&mut self) -> &mut Merger {
+        &mut self.merger
+    }
+
+    /// The universes `leds` LEDs take up.
+    pub fn universes(&self, leds: usize) -> impl Iterator<Item = u16> {
+        let start = self.config.start_universe;
+        (0..self.config.universe_count(leds)).map(move |i| start.saturating_add(i as u16))
+    }
+
+    fn handle_artnet(&mut self, packet: &[u8], from: IpAddr) -> bool {
+        let id = SourceId::Address(from);
+        match artnet::parse(packet) {
+            Some(artnet::Packet::Dmx {
+                universe,
+                sequence,
+                data,
+            }) => {
+                let synced = self
+                    .artnet_synced
+                    .get(&from)
+                    .is_some_and(|at| at.elapsed() < ARTNET_SYNC_TIMEOUT);
+                // a sequence of 0 turns reordering off
+                let sequence = Some(sequence).filter(|s| *s != 0);
+                // held until the next ArtSync, which doesn't name a universe
+                let sync = synced.then_some(0);
+                self.merger
+                    .receive(id, universe, ARTNET_PRIORITY, sequence, sync, data);
+                true
+            }
+            Some(artnet::Packet::Sync) => {
+                self.artnet_synced.insert(from, Instant::now());
+                self.merger.release(id, None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn handle_sacn(&mut self, packet: &[u8]) -> bool {
+        match sacn::parse(packet) {
+            Some(sacn::Packet::Data {
+                cid,
+                priority,
+                sync_universe,
+                sequence,
+                terminated,
+                universe,
+                data,
+            }) => {
+                let id = SourceId::Cid(cid);
+                match terminated {
+                    true => self.merger.terminate(id, universe),
+                    false => {
+                        let sync = Some(sync_universe).filter(|u| *u != 0);
+                        self.merger
+                            .receive(id, universe, priority, Some(sequence), sync, data);
+                    }
+                }
+                true
+            }
+            Some(sacn::Packet::Sync {
+                cid, sync_universe, ..
+            }) => {
+                self.merger.release(SourceId::Cid(cid), Some(sync_universe));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl NetworkInput for DmxInput {
+    fn poll(&mut self) -> Result<usize, SledError> {
+        let mut buffer = [0; 1500];
+        let mut received = 0;
+        for i in 0..self.sockets.len() {
+            loop {
+                let (protocol, socket) = &self.sockets[i];
+                let (length, from) = match socket.recv_from(&mut buffer) {
+                    Ok(packet) => packet,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    // an earlier send bouncing, which says nothing about this socket
+                    Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                    Err(e) => return Err(SledError::from_error(e)),
+                };
+                let handled = match protocol {
+                    DmxProtocol::ArtNet => self.handle_artnet(&buffer[..length], from.ip()),
+                    DmxProtocol::Sacn => self.handle_sacn(&buffer[..length]),
+                };
+                received += handled as usize;
+            }
+        }
+        self.merger.expire();
+        Ok(received)
+    }
+
+    fn apply(&mut self, colors: &mut [Rgb]) {
+        let pixels = self.config.pixels_per_universe();
+        let channels = self.config.channels_per_pixel;
+        let layer = self.config.layer;
+
+        for (i, chunk) in colors.chunks_mut(pixels).enumerate() {
+            let universe = self.config.start_universe.saturating_add(i as u16);
+            let data = self.merger.universe(universe);
+            for (j, color) in chunk.iter_mut().enumerate() {
+                let received = data.map(|data| self.decoder.decode(data, j * channels, channels));
+                layer.blend(color, received);
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+/// How sources sending the same universe at the same priority are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeMode {
+    /// Highest takes precedence: each channel shows the highest level any source sends.
+    #[default]
+    Htp,
+    /// Latest takes precedence: the universe shows whichever source sent last.
+    Ltp,
+}
+
+/// Tells network sources apart: sACN sources by their CID, Art-Net ones by address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SourceId {
+    Cid([u8; 16]),
+    Address(IpAddr),
+}
+
+#[derive(Clone, Debug)]
+struct Source {
+    id: SourceId,
+    priority: u8,
+    sequence: Option<u8>,
+    sync_universe: Option<u16>,
+    data: Vec<u8>,
+    // data waiting for a sync before it's shown
+    pending: Option<Vec<u8>>,
+    last_seen: Instant,
+    // when `data` last changed, counted in packets, for latest takes precedence
+    shown_at: u64,
+}
+
+/// Merges the universes every network source sends into one set of levels.
+///
+/// Only the sources at a universe's highest priority take part, combined by the
+/// `MergeMode`. Sources that go quiet for longer than the timeout are dropped.
+#[derive(Clone, Debug)]
+pub struct Merger {
+    mode: MergeMode,
+    timeout: Duration,
+    universes: BTreeMap<u16, Vec<Source>>,
+    packets: u64,
+    merged: Vec<u8>,
+}
+
+impl Merger {
+    pub fn new(mode: MergeMode, timeout: Duration) -> Self {
+        Merger {
+            mode,
+            timeout,
+            universes: BTreeMap::new(),
+            packets: 0,
+            merged: vec![],
+        }
+    }
+
+    /// Takes a universe of levels from `source`. Data with a `sync_universe` is held
+    /// until `release` is called for it. Returns false if the packet arrived out of
+    /// order and was dropped.
+    pub fn receive(
+        &mut self,
+        id: SourceId,
+        universe: u16,
+        priority: u8,
+        sequence: Option<u8>,
+        sync_universe: Option<u16>,
+        data: &[u8],
+    ) -> bool {
+        let sources = self.universes.entry(universe).or_default();
+        let index = match sources.iter().position(|source| source.id == id) {
+            Some(index) => index,
+            None => {
+                sources.push(Source {
+                    id,
+                    priority,
+                    sequence: None,
+                    sync_universe,
+                    data: vec![],
+                    pending: None,
+                    last_seen: Instant::now(),
+                    shown_at: 0,
+                });
+                sources.len() - 1
+            }
+        };
+        let source = &mut sources[index];
+
+        if let (Some(last), Some(sequence)) = (source.sequence, sequence) {
+            // E1.31's rule: anything up to 20 packets behind the last one is stale
+            let behind = sequence.wrapping_sub(last) as i8;
+            if behind <= 0 && behind > -20 {
+                return false;
+            }
+        }
+
+        self.packets += 1;
+        source.sequence = sequence;
+        source.priority = priority;
+        source.sync_universe = sync_universe;
+        source.last_seen = Instant::now();
+        match sync_universe {
+            Some(_) => source.pending = Some(data.to_vec()),
+            None => {
+                source.data = data.to_vec();
+                source.pending = None;
+                source.shown_at = self.packets;
+            }
+        }
+        true
+    }
+
+    /// Shows the data `source` has been holding for `sync_universe`, or all of it if
+    /// there's no sync universe, as with Art-Net.
+    pub fn release(&mut self, id: SourceId, sync_universe: Option<u16>) {
+        self.packets += 1;
+        let sources = self.universes.values_mut().flatten();
+        for source in sources.filter(|source| source.id == id) {
+            if sync_universe.is_some() && source.sync_universe != sync_universe {
+                continue;
+            }
+            if let Some(pending) = source.pending.take() {
+                source.data = pending;
+                source.shown_at = self.packets;
+            }
+        }
+    }
+
+    /// Drops `source` from `universe` straight away, rather than waiting for it to time out.
+    pub fn terminate(&mut self, id: SourceId, universe: u16) {
+        if let Some(sources) = self.universes.get_mut(&universe) {
+            sources.retain(|source| source.id != id);
+        }
+    }
+
+    /// Drops every source that hasn't been heard from within the timeout.
+    pub fn expire(&mut self) {
+        for sources in self.universes.values_mut() {
+            sources.retain(|source| source.last_seen.elapsed() < self.timeout);
+        }
+        self.universes.retain(|_, sources| !sources.is_empty());
+    }
+
+    /// The merged levels of `universe`, or `None` if no source is sending it.
+    pub fn universe(&mut self, universe: u16) -> Option<&[u8]> {
+        let sources = self.universes.get(&universe)?;
+        let shown = || sources.iter().filter(|source| source.shown_at > 0);
+        let priority = shown().map(|source| source.priority).max()?;
+        let winners = shown().filter(|source| source.priority == priority);
+
+        self.merged.clear();
+        match self.mode {
+            MergeMode::Htp => {
+                for source in winners {
+                    if self.merged.len() < source.data.len() {
+                        self.merged.resize(source.data.len(), 0);
+                    }
+                    for (merged, level) in self.merged.iter_mut().zip(&source.data) {
+                        *merged = (*merged).max(*level);
+                    }
+                }
+            }
+            MergeMode::Ltp => {
+                let latest = winners.max_by_key(|source| source.shown_at)?;
+                self.merged.extend_from_slice(&latest.data);
+            }
+        }
+        Some(&self.merged)
+    }
+
+    /// How many sources are sending `universe`, at any priority.
+    pub fn source_count(&self, universe: u16) -> usize {
+        self.universes.get(&universe).map_or(0, Vec::len)
+    }
+}
@@ -0,0 +1,29 @@
+//! Network protocols for driving pixel controllers from the same frames the strip gets,
+//! and for taking frames from lighting consoles in turn.
+
+pub mod artnet;
+pub mod ddp;
+pub mod dmx;
+pub mod input;
+pub mod merge;
+pub mod opc;
+pub mod realtime;
+pub mod sacn;
+pub mod wled;
+
+use std::net::{SocketAddr, ToSocketAddrs};
+
+use spatial_led::SledError;
+
+/// Looks up `target`, a host with an optional port, using `port` if it has none.
+pub(crate) fn resolve(target: &str, port: u16) -> Result<SocketAddr, SledError> {
+    let addresses = match target.parse::<SocketAddr>() {
+        Ok(address) => return Ok(address),
+        Err(_) if target.contains(':') && !target.contains("::") => target.to_socket_addrs(),
+        Err(_) => (target, port).to_socket_addrs(),
+    };
+    addresses
+        .map_err(|e| SledError::new(format!("Can't resolve `{}`: {}", target, e)))?
+        .next()
+        .ok_or_else(|| SledError::new(format!("`{}` has no addresses.", target)))
+}
@@ -0,0 +1,246 @@
+//! Open Pixel Control: frames of RGB pixels over TCP, as used by Fadecandy and the
+//! tools built around it.
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use palette::rgb::Rgb;
+use spatial_led::{SledError, SledResult};
+
+use super::{
+    input::{Decoder, InputLayer, NetworkInput},
+    resolve,
+};
+use crate::{
+    encode::{Encoder, EncoderConfig},
+    sink::{Frame, LedSink},
+};
+
+pub const PORT: u16 = 7890;
+/// The channel every device listens on.
+pub const BROADCAST: u8 = 0;
+pub const SET_PIXELS: u8 = 0;
+pub const SYSTEM_EXCLUSIVE: u8 = 255;
+
+const HEADER_LENGTH: usize = 4;
+
+/// A message setting the pixels of `channel` to `data`, RGB from the first pixel on.
+pub fn set_pixels(channel: u8, data: &[u8]) -> Vec<u8> {
+    let data = &data[..data.len().min(u16::MAX as usize)];
+    let mut message = Vec::with_capacity(HEADER_LENGTH + data.len());
+    message.push(channel);
+    message.push(SET_PIXELS);
+    message.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    message.extend_from_slice(data);
+    message
+}
+
+/// A message read from a stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Message<'a> {
+    pub channel: u8,
+    pub command: u8,
+    pub data: &'a [u8],
+}
+
+/// Reads the message at the start of `buffer`, along with how many bytes it took up,
+/// or `None` if the whole message hasn't arrived yet.
+pub fn parse(buffer: &[u8]) -> Option<(Message<'_>, usize)> {
+    let header = buffer.get(..HEADER_LENGTH)?;
+    let length = HEADER_LENGTH + u16::from_be_bytes([header[2], header[3]]) as usize;
+    let message = Message {
+        channel: header[0],
+        command: header[1],
+        data: buffer.get(HEADER_LENGTH..length)?,
+    };
+    Some((message, length))
+}
+
+/// Sends frames to an OPC server, such as a Fadecandy's.
+pub struct OpcSink {
+    stream: TcpStream,
+    channel: u8,
+    encoder: Encoder,
+    data: Vec<u8>,
+}
+
+impl OpcSink {
+    /// Connects to `target`, a host with an optional port, to set the pixels of
+    /// `channel`.
+    pub fn connect(target: &str, channel: u8, output: &EncoderConfig) -> Result<Self, SledError> {
+        let address = resolve(target, PORT)?;
+        let stream = TcpStream::connect(address)
+            .map_err(|e| SledError::new(format!("Can't connect to `{}`: {}", target, e)))?;
+        // frames are small and should go out as soon as they're written
+        stream.set_nodelay(true).map_err(SledError::from_error)?;
+        Ok(OpcSink {
+            stream,
+            channel,
+            encoder: Encoder::new(output),
+            data: vec![],
+        })
+    }
+}
+
+impl LedSink for OpcSink {
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        self.data.clear();
+        for pixel in self.encoder.encode(frame.colors.iter()) {
+            self.data.extend_from_slice(&pixel[..3]);
+        }
+        if self.data.len() > u16::MAX as usize {
+            return SledError::new(format!(
+                "OPC can't send {} LEDs in one message.",
+                frame.colors.len()
+            ))
+            .as_err();
+        }
+        self.stream
+            .write_all(&set_pixels(self.channel, &self.data))
+            .map_err(SledError::from_error)
+    }
+}
+
+struct Client {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+/// Accepts OPC clients and keeps the last pixels they've set, mapped to LEDs by index.
+///
+/// Every client's messages are shown as they arrive. Once the last client disconnects,
+/// the LEDs are let go.
+pub struct OpcServer {
+    listener: TcpListener,
+    clients: Vec<Client>,
+    channel: u8,
+    layer: InputLayer,
+    decoder: Decoder,
+    pixels: Vec<Option<[u8; 3]>>,
+}
+
+impl OpcServer {
+    /// Listens on `address` for pixels for the first `leds` LEDs.
+    pub fn bind(
+        address: impl ToSocketAddrs,
+        leds: usize,
+        output: &EncoderConfig,
+    ) -> Result<Self, SledError> {
+        let listener = TcpListener::bind(address).map_err(SledError::from_error)?;
+        listener
+            .set_nonblocking(true)
+            .map_err(SledError::from_error)?;
+        Ok(OpcServer {
+            listener,
+            clients: vec![],
+            channel: BROADCAST,
+            layer: InputLayer::Replace,
+            decoder: Decoder::new(output, Rgb::new(1.0, 1.0, 1.0)),
+            pixels: vec![None; leds],
+        })
+    }
+
+    /// Only shows messages for `channel`, along with broadcasts. The default,
+    /// `BROADCAST`, shows every channel.
+    pub fn with_channel(mut self, channel: u8) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// How received pixels combine with the driver's, `Replace` by default.
+    pub fn with_layer(mut self, layer: InputLayer) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, SledError> {
+        self.listener.local_addr().map_err(SledError::from_error)
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    fn accept(&mut self) -> SledResult {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    stream
+                        .set_nonblocking(true)
+                        .map_err(SledError::from_error)?;
+                    self.clients.push(Client {
+                        stream,
+                        buffer: vec![],
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(SledError::from_error(e)),
+            }
+        }
+    }
+
+    fn show(&mut self, message: Message) -> bool {
+        let wanted = self.channel == BROADCAST
+            || message.channel == BROADCAST
+            || message.channel == self.channel;
+        if !wanted || message.command != SET_PIXELS {
+            return false;
+        }
+        for (pixel, rgb) in self.pixels.iter_mut().zip(message.data.chunks_exact(3)) {
+            *pixel = Some([rgb[0], rgb[1], rgb[2]]);
+        }
+        true
+    }
+}
+
+impl NetworkInput for OpcServer {
+    fn poll(&mut self) -> Result<usize, SledError> {
+        self.accept()?;
+
+        let mut received = 0;
+        let mut chunk = [0; 4096];
+        let mut i = 0;
+        while i < self.clients.len() {
+            let connected = loop {
+                match self.clients[i].stream.read(&mut chunk) {
+                    Ok(0) => break false,
+                    Ok(length) => self.clients[i].buffer.extend_from_slice(&chunk[..length]),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break true,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(_) => break false,
+                }
+            };
+
+            let buffer = std::mem::take(&mut self.clients[i].buffer);
+            let mut used = 0;
+            while let Some((message, length)) = parse(&buffer[used..]) {
+                received += self.show(message) as usize;
+                used += length;
+            }
+            self.clients[i].buffer = buffer;
+            self.clients[i].buffer.drain(..used);
+
+            match connected {
+                true => i += 1,
+                false => {
+                    self.clients.swap_remove(i);
+                }
+            }
+        }
+
+        if self.clients.is_empty() {
+            self.pixels.fill(None);
+        }
+        Ok(received)
+    }
+
+    fn apply(&mut self, colors: &mut [Rgb]) {
+        for (i, color) in colors.iter_mut().enumerate() {
+            let received = self.pixels.get(i).copied().flatten();
+            let received = received.map(|pixel| self.decoder.decode(&pixel, 0, 3));
+            self.layer.blend(color, received);
+        }
+    }
+}
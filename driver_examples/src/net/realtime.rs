@@ -0,0 +1,352 @@
+use std::{
+    fmt,
+    io::ErrorKind,
+    net::{Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use palette::rgb::Rgb;
+use serde::Deserialize;
+use spatial_led::{SledError, SledResult};
+use toml::{Table, Value};
+
+use super::{
+    ddp,
+    input::{Decoder, InputLayer, NetworkInput},
+    resolve, wled,
+};
+use crate::{
+    encode::{Encoder, EncoderConfig},
+    params::FromTable,
+    sink::{Frame, LedSink},
+    strip::kelvin_to_rgb,
+};
+
+/// The pixel streaming protocols WLED devices understand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RealtimeProtocol {
+    Ddp,
+    /// WLED's own realtime UDP, sent as DRGB or DNRGB.
+    Wled,
+}
+
+impl RealtimeProtocol {
+    pub fn port(self) -> u16 {
+        match self {
+            RealtimeProtocol::Ddp => ddp::PORT,
+            RealtimeProtocol::Wled => wled::PORT,
+        }
+    }
+}
+
+impl FromStr for RealtimeProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ddp" => Ok(RealtimeProtocol::Ddp),
+            "wled" => Ok(RealtimeProtocol::Wled),
+            _ => Err(format!("Unknown protocol `{}`. Expected ddp or wled.", s)),
+        }
+    }
+}
+
+impl fmt::Display for RealtimeProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RealtimeProtocol::Ddp => write!(f, "ddp"),
+            RealtimeProtocol::Wled => write!(f, "wled"),
+        }
+    }
+}
+
+/// How frames are streamed to and from WLED devices.
+///
+/// Loaded from the `[realtime]` table of a params file:
+///
+/// ```toml
+/// [realtime]
+/// timeout = 2.5             # seconds a device holds the stream after the last frame
+/// layer = "over"            # replace, over or htp, when receiving
+/// white_temperature = 4000  # kelvin, for received RGBW pixels
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RealtimeConfig {
+    pub timeout: f32,
+    pub layer: InputLayer,
+    pub white_temperature: f32,
+}
+
+impl Default for RealtimeConfig {
+    fn default() -> Self {
+        RealtimeConfig {
+            timeout: 2.5,
+            layer: InputLayer::default(),
+            white_temperature: 4000.0,
+        }
+    }
+}
+
+impl FromTable for RealtimeConfig {
+    fn from_table(table: &Table) -> Result<Self, SledError> {
+        let config: RealtimeConfig = Value::Table(table.clone())
+            .try_into()
+            .map_err(|e| SledError::new(format!("Invalid [realtime] table: {}", e)))?;
+
+        if !(config.timeout > 0.0 && config.timeout.is_finite()) {
+            return SledError::new(format!(
+                "[realtime] timeout should be a positive number of seconds, found {}.",
+                config.timeout
+            ))
+            .as_err();
+        }
+        if !(1000.0..=40_000.0).contains(&config.white_temperature) {
+            return SledError::new(format!(
+                "[realtime] white_temperature should be between 1000 and 40000 kelvin, found {}.",
+                config.white_temperature
+            ))
+            .as_err();
+        }
+        Ok(config)
+    }
+}
+
+impl RealtimeConfig {
+    /// The timeout in the whole seconds WLED packets carry.
+    pub fn wled_timeout(&self) -> u8 {
+        self.timeout
+            .ceil()
+            .clamp(1.0, (wled::NO_TIMEOUT - 1) as f32) as u8
+    }
+}
+
+/// Streams frames to a WLED device, or anything else speaking DDP.
+pub struct RealtimeSink {
+    protocol: RealtimeProtocol,
+    socket: UdpSocket,
+    target: SocketAddr,
+    encoder: Encoder,
+    timeout: u8,
+    sequence: u8,
+    data: Vec<u8>,
+}
+
+impl RealtimeSink {
+    /// Sends to `target`, a host with an optional port.
+    pub fn new(
+        protocol: RealtimeProtocol,
+        target: &str,
+        config: &RealtimeConfig,
+        output: &EncoderConfig,
+    ) -> Result<Self, SledError> {
+        let target = resolve(target, protocol.port())?;
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(SledError::from_error)?;
+        Ok(RealtimeSink {
+            protocol,
+            socket,
+            target,
+            encoder: Encoder::new(output),
+            timeout: config.wled_timeout(),
+            sequence: 0,
+            data: vec![],
+        })
+    }
+}
+
+impl LedSink for RealtimeSink {
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        self.data.clear();
+        for pixel in self.encoder.encode(frame.colors.iter()) {
+            self.data.extend_from_slice(&pixel[..3]);
+        }
+
+        let packets = match self.protocol {
+            RealtimeProtocol::Ddp => {
+                // DDP counts 1 to 15, leaving 0 for senders that don't count
+                self.sequence = self.sequence % 15 + 1;
+                ddp::frame_packets(self.sequence, &self.data, false)
+            }
+            RealtimeProtocol::Wled => {
+                if frame.colors.len() > u16::MAX as usize + wled::MAX_DNRGB {
+                    return SledError::new(format!(
+                        "WLED can't address {} LEDs.",
+                        frame.colors.len()
+                    ))
+                    .as_err();
+                }
+                wled::frame_packets(self.timeout, &self.data)
+            }
+        };
+        for packet in packets {
+            self.socket
+                .send_to(&packet, self.target)
+                .map_err(SledError::from_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Listens for DDP and WLED realtime packets, so WLED tools and other devices can
+/// drive the LEDs.
+///
+/// Pixels are mapped to LEDs by index. A stream holds the LEDs until it has been
+/// quiet for its timeout, which WLED packets carry and DDP takes from the config. DDP
+/// data is shown once a packet with the push flag arrives.
+pub struct RealtimeInput {
+    config: RealtimeConfig,
+    sockets: Vec<(RealtimeProtocol, UdpSocket)>,
+    decoder: Decoder,
+    // RGBW, with white 0 for RGB pixels
+    shown: Vec<Option<[u8; 4]>>,
+    pending: Vec<Option<[u8; 4]>>,
+    // when the stream was last heard from, and how long it holds the LEDs for
+    live: Option<(Instant, Duration)>,
+}
+
+impl RealtimeInput {
+    /// Receives pixels for the first `leds` LEDs.
+    pub fn new(config: RealtimeConfig, output: &EncoderConfig, leds: usize) -> Self {
+        RealtimeInput {
+            decoder: Decoder::new(output, kelvin_to_rgb(config.white_temperature)),
+            config,
+            sockets: vec![],
+            shown: vec![None; leds],
+            pending: vec![None; leds],
+            live: None,
+        }
+    }
+
+    /// Listens for `protocol` on its usual port.
+    pub fn listen(&mut self, protocol: RealtimeProtocol) -> SledResult {
+        self.bind(protocol, (Ipv4Addr::UNSPECIFIED, protocol.port()))
+            .map(|_| ())
+    }
+
+    /// Listens for `protocol` on `address`, returning the address bound to.
+    pub fn bind(
+        &mut self,
+        protocol: RealtimeProtocol,
+        address: impl ToSocketAddrs,
+    ) -> Result<SocketAddr, SledError> {
+        let socket = UdpSocket::bind(address).map_err(SledError::from_error)?;
+        socket
+            .set_nonblocking(true)
+            .map_err(SledError::from_error)?;
+        let address = socket.local_addr().map_err(SledError::from_error)?;
+        self.sockets.push((protocol, socket));
+        Ok(address)
+    }
+
+    pub fn config(&self) -> &RealtimeConfig {
+        &self.config
+    }
+
+    /// Whether a stream is holding the LEDs.
+    pub fn is_live(&self) -> bool {
+        self.live
+            .is_some_and(|(since, timeout)| since.elapsed() < timeout)
+    }
+
+    fn hold_for(&mut self, timeout: Duration) {
+        self.live = Some((Instant::now(), timeout));
+    }
+
+    fn handle_ddp(&mut self, packet: &[u8]) -> bool {
+        let Some(packet) = ddp::parse(packet) else {
+            return false;
+        };
+        let size = if packet.white { 4 } else { 3 };
+        let first = packet.offset / size;
+        let pixels = packet.data.chunks_exact(size);
+        for (pending, pixel) in self.pending.iter_mut().skip(first).zip(pixels) {
+            *pending = Some(rgbw(pixel));
+        }
+        if packet.push {
+            self.shown.clone_from(&self.pending);
+        }
+        self.hold_for(Duration::from_secs_f32(self.config.timeout));
+        true
+    }
+
+    fn handle_wled(&mut self, packet: &[u8]) -> bool {
+        let Some(packet) = wled::parse(packet) else {
+            return false;
+        };
+        match packet.pixels {
+            wled::Pixels::Indexed(data) => {
+                for pixel in data.chunks_exact(4) {
+                    if let Some(shown) = self.shown.get_mut(pixel[0] as usize) {
+                        *shown = Some(rgbw(&pixel[1..]));
+                    }
+                }
+            }
+            wled::Pixels::Run { start, white, data } => {
+                let pixels = data.chunks_exact(if white { 4 } else { 3 });
+                for (shown, pixel) in self.shown.iter_mut().skip(start).zip(pixels) {
+                    *shown = Some(rgbw(pixel));
+                }
+            }
+        }
+        let timeout = match packet.timeout {
+            // close enough to forever
+            wled::NO_TIMEOUT => Duration::from_secs(u32::MAX as u64),
+            seconds => Duration::from_secs(seconds.max(1) as u64),
+        };
+        self.hold_for(timeout);
+        true
+    }
+}
+
+impl NetworkInput for RealtimeInput {
+    fn poll(&mut self) -> Result<usize, SledError> {
+        let mut buffer = [0; 1500];
+        let mut received = 0;
+        for i in 0..self.sockets.len() {
+            loop {
+                let (protocol, socket) = &self.sockets[i];
+                let length = match socket.recv(&mut buffer) {
+                    Ok(length) => length,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    // an earlier send bouncing, which says nothing about this socket
+                    Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                    Err(e) => return Err(SledError::from_error(e)),
+                };
+                let handled = match protocol {
+                    RealtimeProtocol::Ddp => self.handle_ddp(&buffer[..length]),
+                    RealtimeProtocol::Wled => self.handle_wled(&buffer[..length]),
+                };
+                received += handled as usize;
+            }
+        }
+
+        if self.live.is_some() && !self.is_live() {
+            self.live = None;
+            self.shown.fill(None);
+            self.pending.fill(None);
+        }
+        Ok(received)
+    }
+
+    fn apply(&mut self, colors: &mut [Rgb]) {
+        let live = self.is_live();
+        for (i, color) in colors.iter_mut().enumerate() {
+            let received = match live {
+                true => self.shown.get(i).copied().flatten(),
+                false => None,
+            };
+            let received = received.map(|pixel| self.decoder.decode(&pixel, 0, 4));
+            self.config.layer.blend(color, received);
+        }
+    }
+}
+
+fn rgbw(pixel: &[u8]) -> [u8; 4] {
+    [
+        pixel[0],
+        pixel[1],
+        pixel[2],
+        pixel.get(3).copied().unwrap_or(0),
+    ]
+}
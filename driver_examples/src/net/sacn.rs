@@ -0,0 +1,168 @@
+//! ANSI E1.31 (streaming ACN) data and synchronization packets.
+
+pub const PORT: u16 = 5568;
+
+const PACKET_ID: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_DATA: u32 = 0x0000_0004;
+const VECTOR_ROOT_EXTENDED: u32 = 0x0000_0008;
+const VECTOR_FRAMING_DATA: u32 = 0x0000_0002;
+const VECTOR_FRAMING_SYNC: u32 = 0x0000_0001;
+const VECTOR_DMP_SET_PROPERTY: u8 = 0x02;
+const SOURCE_NAME_LENGTH: usize = 64;
+const OPTION_PREVIEW: u8 = 0x80;
+const OPTION_TERMINATED: u8 = 0x40;
+
+/// The highest universe E1.31 allows; 0 is reserved.
+pub const MAX_UNIVERSE: u16 = 63999;
+
+/// Where a universe is multicast to, `239.255.<high byte>.<low byte>`.
+pub fn multicast_address(universe: u16) -> [u8; 4] {
+    let [high, low] = universe.to_be_bytes();
+    [239, 255, high, low]
+}
+
+/// Identifies a sender to receivers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Source {
+    /// A unique id for this sender, kept for as long as it runs.
+    pub cid: [u8; 16],
+    pub name: String,
+    /// 0 to 200, receivers follow the highest priority source of a universe.
+    pub priority: u8,
+}
+
+// flags (0x7) in the top 4 bits, then a 12-bit length counted from the field itself
+fn flags_and_length(length: usize) -> [u8; 2] {
+    (0x7000 | (length as u16 & 0x0fff)).to_be_bytes()
+}
+
+fn root_layer(packet: &mut Vec<u8>, vector: u32, cid: &[u8; 16]) {
+    packet.extend_from_slice(&0x0010u16.to_be_bytes()); // preamble size
+    packet.extend_from_slice(&0u16.to_be_bytes()); // postamble size
+    packet.extend_from_slice(PACKET_ID);
+    packet.extend_from_slice(&[0, 0]); // length, filled in by `finish`
+    packet.extend_from_slice(&vector.to_be_bytes());
+    packet.extend_from_slice(cid);
+}
+
+// fills in the length of each layer, given where each one's flags start
+fn finish(packet: &mut [u8], layers: &[usize]) {
+    let total = packet.len();
+    for &at in layers {
+        packet[at..at + 2].copy_from_slice(&flags_and_length(total - at));
+    }
+}
+
+/// A data packet for `universe`, holding up to 512 channels. A nonzero `sync_universe`
+/// makes receivers wait for a sync packet on it before showing the data.
+pub fn data_packet(
+    source: &Source,
+    universe: u16,
+    sequence: u8,
+    sync_universe: u16,
+    data: &[u8],
+) -> Vec<u8> {
+    let data = &data[..data.len().min(512)];
+    let mut packet = Vec::with_capacity(126 + data.len());
+    root_layer(&mut packet, VECTOR_ROOT_DATA, &source.cid);
+
+    packet.extend_from_slice(&[0, 0]);
+    packet.extend_from_slice(&VECTOR_FRAMING_DATA.to_be_bytes());
+    let mut name = [0; SOURCE_NAME_LENGTH];
+    // leave room for the terminating null
+    let length = source.name.len().min(SOURCE_NAME_LENGTH - 1);
+    name[..length].copy_from_slice(&source.name.as_bytes()[..length]);
+    packet.extend_from_slice(&name);
+    packet.push(source.priority.min(200));
+    packet.extend_from_slice(&sync_universe.to_be_bytes());
+    packet.push(sequence);
+    packet.push(0); // options
+    packet.extend_from_slice(&universe.to_be_bytes());
+
+    packet.extend_from_slice(&[0, 0]);
+    packet.push(VECTOR_DMP_SET_PROPERTY);
+    packet.push(0xa1); // address and data type
+    packet.extend_from_slice(&0u16.to_be_bytes()); // first property address
+    packet.extend_from_slice(&1u16.to_be_bytes()); // address increment
+    packet.extend_from_slice(&(data.len() as u16 + 1).to_be_bytes());
+    packet.push(0); // DMX start code
+    packet.extend_from_slice(data);
+
+    finish(&mut packet, &[16, 38, 115]);
+    packet
+}
+
+/// A synchronization packet, telling receivers to show the data sent for `sync_universe`.
+pub fn sync_packet(cid: &[u8; 16], sequence: u8, sync_universe: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(49);
+    root_layer(&mut packet, VECTOR_ROOT_EXTENDED, cid);
+
+    packet.extend_from_slice(&[0, 0]);
+    packet.extend_from_slice(&VECTOR_FRAMING_SYNC.to_be_bytes());
+    packet.push(sequence);
+    packet.extend_from_slice(&sync_universe.to_be_bytes());
+    packet.extend_from_slice(&[0, 0]); // reserved
+
+    finish(&mut packet, &[16, 38]);
+    packet
+}
+
+/// An incoming E1.31 packet this crate understands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Packet<'a> {
+    Data {
+        cid: [u8; 16],
+        priority: u8,
+        sync_universe: u16,
+        sequence: u8,
+        /// The source has stopped sending this universe.
+        terminated: bool,
+        universe: u16,
+        data: &'a [u8],
+    },
+    Sync {
+        cid: [u8; 16],
+        sequence: u8,
+        sync_universe: u16,
+    },
+}
+
+/// Reads a data or synchronization packet, or `None` for anything else. Preview data
+/// and alternate start codes, which don't carry levels for fixtures, are skipped too.
+pub fn parse(packet: &[u8]) -> Option<Packet<'_>> {
+    if packet.len() < 44 || packet[4..16] != *PACKET_ID {
+        return None;
+    }
+    let u16_at = |at: usize| u16::from_be_bytes([packet[at], packet[at + 1]]);
+    let u32_at = |at: usize| u32::from_be_bytes(packet[at..at + 4].try_into().unwrap());
+    let cid = packet[22..38].try_into().ok()?;
+
+    match (u32_at(18), u32_at(40)) {
+        (VECTOR_ROOT_DATA, VECTOR_FRAMING_DATA) if packet.len() >= 126 => {
+            let options = packet[112];
+            if options & OPTION_PREVIEW != 0
+                || packet[117] != VECTOR_DMP_SET_PROPERTY
+                || packet[125] != 0
+            {
+                return None;
+            }
+            // the count includes the start code
+            let count = (u16_at(123) as usize).clamp(1, 513);
+            Some(Packet::Data {
+                cid,
+                priority: packet[108],
+                sync_universe: u16_at(109),
+                sequence: packet[111],
+                terminated: options & OPTION_TERMINATED != 0,
+                universe: u16_at(113),
+                data: packet.get(126..125 + count)?,
+            })
+        }
+        (VECTOR_ROOT_EXTENDED, VECTOR_FRAMING_SYNC) if packet.len() >= 47 => Some(Packet::Sync {
+            cid,
+            sequence: packet[44],
+            sync_universe: u16_at(45),
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,92 @@
+//! WLED's realtime UDP protocols: WARLS, DRGB, DRGBW and DNRGB.
+
+pub const PORT: u16 = 21324;
+/// The most LEDs a DRGB packet carries.
+pub const MAX_DRGB: usize = 490;
+/// The most LEDs a DNRGB packet carries.
+pub const MAX_DNRGB: usize = 489;
+/// Sent as the timeout to keep a device in realtime mode until it's told otherwise.
+pub const NO_TIMEOUT: u8 = 255;
+
+const WARLS: u8 = 1;
+const DRGB: u8 = 2;
+const DRGBW: u8 = 3;
+const DNRGB: u8 = 4;
+
+/// A DRGB packet, RGB for every LED from the first. `timeout` is how many seconds the
+/// device waits after the last packet before going back to its own effects.
+pub fn drgb_packet(timeout: u8, data: &[u8]) -> Vec<u8> {
+    let data = &data[..data.len().min(MAX_DRGB * 3)];
+    let mut packet = vec![DRGB, timeout];
+    packet.extend_from_slice(data);
+    packet
+}
+
+/// A DNRGB packet, RGB for LEDs from `start` on.
+pub fn dnrgb_packet(timeout: u8, start: u16, data: &[u8]) -> Vec<u8> {
+    let data = &data[..data.len().min(MAX_DNRGB * 3)];
+    let mut packet = vec![DNRGB, timeout];
+    packet.extend_from_slice(&start.to_be_bytes());
+    packet.extend_from_slice(data);
+    packet
+}
+
+/// Packs a whole frame of RGB, in one DRGB packet if it fits or as many DNRGB
+/// packets as it takes otherwise.
+pub fn frame_packets(timeout: u8, data: &[u8]) -> Vec<Vec<u8>> {
+    if data.len() <= MAX_DRGB * 3 {
+        return vec![drgb_packet(timeout, data)];
+    }
+    data.chunks(MAX_DNRGB * 3)
+        .enumerate()
+        .map(|(i, chunk)| dnrgb_packet(timeout, (i * MAX_DNRGB) as u16, chunk))
+        .collect()
+}
+
+/// An incoming realtime packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Packet<'a> {
+    /// Seconds to hold the received colors for, or `NO_TIMEOUT`.
+    pub timeout: u8,
+    pub pixels: Pixels<'a>,
+}
+
+/// The LEDs a realtime packet sets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pixels<'a> {
+    /// Groups of index, red, green and blue.
+    Indexed(&'a [u8]),
+    /// RGB, or RGBW if `white`, for consecutive LEDs from `start`.
+    Run {
+        start: usize,
+        white: bool,
+        data: &'a [u8],
+    },
+}
+
+/// Reads a WARLS, DRGB, DRGBW or DNRGB packet, or `None` for anything else, such as
+/// WLED's sync notifications.
+pub fn parse(packet: &[u8]) -> Option<Packet<'_>> {
+    let (&protocol, rest) = packet.split_first()?;
+    let (&timeout, data) = rest.split_first()?;
+    let pixels = match protocol {
+        WARLS => Pixels::Indexed(data),
+        DRGB => Pixels::Run {
+            start: 0,
+            white: false,
+            data,
+        },
+        DRGBW => Pixels::Run {
+            start: 0,
+            white: true,
+            data,
+        },
+        DNRGB if data.len() >= 2 => Pixels::Run {
+            start: u16::from_be_bytes([data[0], data[1]]) as usize,
+            white: false,
+            data: &data[2..],
+        },
+        _ => return None,
+    };
+    Some(Packet { timeout, pixels })
+}
@@ -0,0 +1,396 @@
+use std::{fmt, fs, ops::RangeInclusive, path::Path};
+
+use spatial_led::{driver::Data, SledError, SledResult, Vec2};
+
+use crate::{
+    encode::EncoderConfig,
+    net::{dmx::DmxConfig, realtime::RealtimeConfig},
+    post::PostProcess,
+    power::PowerConfig,
+    registry, rng,
+    strip::StripConfig,
+    wiring::WiringConfig,
+};
+use toml::{Table, Value};
+
+/// A single tunable value an effect exposes.
+///
+/// Each variant is stored in the driver's `Data` as its plain type (`f32`, `usize` or
+/// `Vec2`), under the parameter's name, so effects can read it back every frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParamValue {
+    Float(f32),
+    Count(usize),
+    Vec2(Vec2),
+}
+
+impl ParamValue {
+    pub fn store(self, data: &mut Data, key: &str) {
+        match self {
+            ParamValue::Float(value) => data.set(key, value),
+            ParamValue::Count(value) => data.set(key, value),
+            ParamValue::Vec2(value) => data.set(key, value),
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ParamValue::Float(_) => "float",
+            ParamValue::Count(_) => "count",
+            ParamValue::Vec2(_) => "vec2",
+        }
+    }
+
+    fn to_toml(self) -> Value {
+        match self {
+            ParamValue::Float(value) => Value::Float(value as f64),
+            ParamValue::Count(value) => Value::Integer(value as i64),
+            ParamValue::Vec2(value) => Value::Array(vec![
+                Value::Float(value.x as f64),
+                Value::Float(value.y as f64),
+            ]),
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamValue::Float(value) => write!(f, "{:.3}", value),
+            ParamValue::Count(value) => write!(f, "{}", value),
+            ParamValue::Vec2(value) => write!(f, "({:.3}, {:.3})", value.x, value.y),
+        }
+    }
+}
+
+/// Declares one of an effect's parameters: its name, type, valid range and default.
+///
+/// For `Vec2` parameters the range applies to each component.
+#[derive(Clone, Debug)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub default: ParamValue,
+    pub range: RangeInclusive<f32>,
+}
+
+impl ParamSpec {
+    pub const fn float(name: &'static str, default: f32, range: RangeInclusive<f32>) -> Self {
+        ParamSpec {
+            name,
+            default: ParamValue::Float(default),
+            range,
+        }
+    }
+
+    pub const fn count(name: &'static str, default: usize, range: RangeInclusive<usize>) -> Self {
+        ParamSpec {
+            name,
+            default: ParamValue::Count(default),
+            range: *range.start() as f32..=*range.end() as f32,
+        }
+    }
+
+    pub const fn vec2(name: &'static str, default: Vec2, range: RangeInclusive<f32>) -> Self {
+        ParamSpec {
+            name,
+            default: ParamValue::Vec2(default),
+            range,
+        }
+    }
+
+    /// Reads the parameter's current value out of a driver's `Data`.
+    pub fn load(&self, data: &Data) -> Result<ParamValue, SledError> {
+        Ok(match self.default {
+            ParamValue::Float(_) => ParamValue::Float(*data.get(self.name)?),
+            ParamValue::Count(_) => ParamValue::Count(*data.get(self.name)?),
+            ParamValue::Vec2(_) => ParamValue::Vec2(*data.get(self.name)?),
+        })
+    }
+
+    /// Pulls a value back inside this parameter's range.
+    pub fn clamp(&self, value: ParamValue) -> ParamValue {
+        let (min, max) = (*self.range.start(), *self.range.end());
+        match value {
+            ParamValue::Float(v) => ParamValue::Float(v.clamp(min, max)),
+            ParamValue::Count(v) => ParamValue::Count(v.clamp(min as usize, max as usize)),
+            ParamValue::Vec2(v) => ParamValue::Vec2(v.clamp(Vec2::splat(min), Vec2::splat(max))),
+        }
+    }
+
+    /// Steps a value by `steps` hundredths of this parameter's range, staying in range.
+    /// `component` picks the axis to change for `Vec2` values.
+    pub fn nudge(&self, value: ParamValue, component: usize, steps: i32) -> ParamValue {
+        let step = (self.range.end() - self.range.start()) / 100.0;
+        let nudged = match value {
+            ParamValue::Float(v) => ParamValue::Float(v + step * steps as f32),
+            ParamValue::Count(v) => {
+                let step = step.round().max(1.0) as i64;
+                ParamValue::Count((v as i64 + step * steps as i64).max(0) as usize)
+            }
+            ParamValue::Vec2(mut v) => {
+                v[component] += step * steps as f32;
+                ParamValue::Vec2(v)
+            }
+        };
+        self.clamp(nudged)
+    }
+
+    fn parse(&self, value: &Value) -> Result<ParamValue, SledError> {
+        let number = |value: &Value| match value {
+            Value::Float(v) => Some(*v as f32),
+            Value::Integer(v) => Some(*v as f32),
+            _ => None,
+        };
+
+        let parsed = match (self.default, value) {
+            (ParamValue::Float(_), value) => number(value).map(ParamValue::Float),
+            (ParamValue::Count(_), Value::Integer(v)) if *v >= 0 => {
+                Some(ParamValue::Count(*v as usize))
+            }
+            (ParamValue::Vec2(_), Value::Array(items)) if items.len() == 2 => {
+                match (number(&items[0]), number(&items[1])) {
+                    (Some(x), Some(y)) => Some(ParamValue::Vec2(Vec2::new(x, y))),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        let parsed = parsed.ok_or_else(|| {
+            SledError::new(format!(
+                "Parameter `{}` expects a {}, found `{}`.",
+                self.name,
+                self.default.type_name(),
+                value
+            ))
+        })?;
+
+        if self.clamp(parsed) != parsed {
+            return SledError::new(format!(
+                "Parameter `{}` must be within {:?}, found `{}`.",
+                self.name, self.range, value
+            ))
+            .as_err();
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Stores each parameter's default value, skipping any that were already set
+/// (for example by a `ParamFile` applied before the driver was mounted).
+pub fn store_defaults(data: &mut Data, specs: &[ParamSpec]) {
+    for spec in specs {
+        if data.empty_at(spec.name) {
+            spec.default.store(data, spec.name);
+        }
+    }
+}
+
+/// Validates a table of `name = value` pairs against `specs` and writes them into `data`.
+pub fn apply_table(
+    table: &Table,
+    effect: &str,
+    specs: &[ParamSpec],
+    data: &mut Data,
+) -> SledResult {
+    for (name, value) in table {
+        let spec = specs.iter().find(|spec| spec.name == name).ok_or_else(|| {
+            SledError::new(format!("`{}` has no parameter named `{}`.", effect, name))
+        })?;
+        spec.parse(value)?.store(data, spec.name);
+    }
+    Ok(())
+}
+
+/// A settings table of a params file, such as `[post]`, read into its config.
+pub trait FromTable: Sized {
+    fn from_table(table: &Table) -> Result<Self, SledError>;
+}
+
+/// Checks a settings table without keeping the result.
+type Check = fn(&Table) -> SledResult;
+
+fn check<T: FromTable>(table: &Table) -> SledResult {
+    T::from_table(table).map(|_| ())
+}
+
+/// Every settings table a params file can have, with what checks it. Any other table
+/// holds an effect's parameters.
+const SECTIONS: &[(&str, Check)] = &[
+    ("post", check::<PostProcess>),
+    ("power", check::<PowerConfig>),
+    ("output", check::<EncoderConfig>),
+    ("strip", check::<StripConfig>),
+    ("wiring", check::<WiringConfig>),
+    ("dmx", check::<DmxConfig>),
+    ("realtime", check::<RealtimeConfig>),
+];
+
+/// Parameter values for any number of effects, keyed by effect name, along with an
+/// optional seed shared by all of them, the `[post]` adjustments described in
+/// `PostProcess`, the `[power]` limits described in `PowerConfig`, the `[output]`
+/// encoding described in `EncoderConfig`, the `[strip]` hardware described in
+/// `StripConfig`, the `[wiring]` of data lines described in `WiringConfig`, the
+/// `[dmx]` universe layout described in `DmxConfig` and the `[realtime]` streaming
+/// described in `RealtimeConfig`.
+///
+/// ```toml
+/// seed = 42
+///
+/// [warpspeed]
+/// velocity = 9.0
+/// direction = [0.0, -1.0]
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ParamFile {
+    table: Table,
+}
+
+impl ParamFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SledError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|e| SledError::new(format!("Failed to read `{}`: {}", path.display(), e)))?;
+        Self::parse(&source)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, SledError> {
+        let source: Table = source.parse().map_err(SledError::from_error)?;
+        let mut table = Table::new();
+        for (name, value) in source {
+            match (name.as_str(), &value) {
+                ("seed", Value::Integer(seed)) if *seed >= 0 => {}
+                ("seed", _) => {
+                    return SledError::from("`seed` should be a non-negative integer.").as_err()
+                }
+                (_, Value::Table(contents)) => {
+                    if let Some((_, check)) = SECTIONS.iter().find(|(section, _)| *section == name)
+                    {
+                        check(contents)?;
+                        table.insert(name, value);
+                        continue;
+                    }
+                    // anything else is an effect's parameters, checked now rather than
+                    // whenever that effect happens to be built
+                    let effect = registry::find(&name).ok_or_else(|| {
+                        let sections: Vec<&str> = SECTIONS.iter().map(|(name, _)| *name).collect();
+                        SledError::new(format!(
+                            "Unknown table `[{}]`. Expected an effect name or one of {}.",
+                            name,
+                            sections.join(", ")
+                        ))
+                    })?;
+                    apply_table(contents, effect.name, effect.params, &mut Data::new())?;
+                    // effects are named case-insensitively, so file them under the
+                    // registry's name for `apply` and `record` to find
+                    if table.contains_key(effect.name) {
+                        return SledError::new(format!(
+                            "`{}` has more than one table of parameters.",
+                            effect.name
+                        ))
+                        .as_err();
+                    }
+                    table.insert(effect.name.to_string(), value);
+                    continue;
+                }
+                _ => {
+                    return SledError::new(format!("`{}` should be a table of parameters.", name))
+                        .as_err()
+                }
+            }
+            table.insert(name, value);
+        }
+        Ok(ParamFile { table })
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.table
+            .get("seed")
+            .and_then(Value::as_integer)
+            .map(|seed| seed as u64)
+    }
+
+    /// The `[post]` adjustments, or none if the file has no such table.
+    pub fn post(&self) -> PostProcess {
+        self.section("post")
+    }
+
+    /// The `[power]` model, or the default estimate-only one if there's no such table.
+    pub fn power(&self) -> PowerConfig {
+        self.section("power")
+    }
+
+    /// How frames are encoded for hardware, from the `[output]` table.
+    pub fn output(&self) -> EncoderConfig {
+        self.section("output")
+    }
+
+    /// The strip being driven, from the `[strip]` table.
+    pub fn strip(&self) -> StripConfig {
+        self.section("strip")
+    }
+
+    /// Which LEDs go out on which data line, from the `[wiring]` table.
+    pub fn wiring(&self) -> WiringConfig {
+        self.section("wiring")
+    }
+
+    /// How frames are split into Art-Net or sACN universes, from the `[dmx]` table.
+    pub fn dmx(&self) -> DmxConfig {
+        self.section("dmx")
+    }
+
+    /// How frames are streamed to and from WLED devices, from the `[realtime]` table.
+    pub fn realtime(&self) -> RealtimeConfig {
+        self.section("realtime")
+    }
+
+    /// The settings table called `name`, or the defaults if the file doesn't have one.
+    fn section<T: FromTable + Default>(&self, name: &str) -> T {
+        match self.table.get(name).and_then(Value::as_table) {
+            // `parse` already checked it, and nothing else writes settings tables
+            Some(table) => T::from_table(table).expect("settings tables are checked by parse"),
+            None => T::default(),
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.table
+            .insert("seed".to_string(), Value::Integer(seed as i64));
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> SledResult {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .map_err(|e| SledError::new(format!("Failed to write `{}`: {}", path.display(), e)))
+    }
+
+    /// Validates the values listed for `effect` and writes them into `data`, seeding the
+    /// driver too if a seed is set.
+    pub fn apply(&self, effect: &str, specs: &[ParamSpec], data: &mut Data) -> SledResult {
+        if let Some(seed) = self.seed() {
+            rng::set_seed(data, seed);
+        }
+        match self.table.get(effect).and_then(Value::as_table) {
+            Some(table) => apply_table(table, effect, specs, data),
+            None => Ok(()),
+        }
+    }
+
+    /// Captures the current value of every parameter in `data` under `effect`.
+    pub fn record(&mut self, effect: &str, specs: &[ParamSpec], data: &Data) -> SledResult {
+        let mut table = Table::new();
+        for spec in specs {
+            table.insert(spec.name.to_string(), spec.load(data)?.to_toml());
+        }
+        self.table.insert(effect.to_string(), Value::Table(table));
+        Ok(())
+    }
+}
+
+impl fmt::Display for ParamFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.table)
+    }
+}
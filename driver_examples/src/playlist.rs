@@ -0,0 +1,280 @@
+use std::{fs, path::Path, time::Duration};
+
+use palette::rgb::Rgb;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    seq::SliceRandom,
+    Rng, SeedableRng,
+};
+use serde::Deserialize;
+use spatial_led::{driver::Data, Sled, SledError};
+use toml::Table;
+
+use crate::{
+    clock::Driver,
+    params::{self, ParamFile},
+    registry::{self, EffectInfo},
+    rng,
+    transition::{Blend, Crossfader},
+};
+
+/// The order a playlist's entries are played in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistMode {
+    /// Plays entries top to bottom, then starts over.
+    #[default]
+    Sequential,
+    /// Plays every entry once in a random order, then reshuffles.
+    Shuffle,
+    /// Picks each next entry at random, favoring those with larger weights.
+    Weighted,
+}
+
+/// One effect in a playlist, along with how long it plays and its parameter preset.
+#[derive(Clone, Debug)]
+pub struct PlaylistEntry {
+    pub effect: &'static EffectInfo,
+    pub duration: Duration,
+    pub weight: f32,
+    params: Table,
+}
+
+impl PlaylistEntry {
+    /// Builds the entry's effect, applying `base` first and then the entry's own preset.
+    pub fn build_driver(&self, base: &ParamFile) -> Result<Driver<Rgb>, SledError> {
+        let mut driver = self.effect.build_driver_with(base)?;
+        params::apply_table(
+            &self.params,
+            self.effect.name,
+            self.effect.params,
+            driver.data_mut(),
+        )?;
+        Ok(driver)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlaylistFile {
+    #[serde(default)]
+    mode: PlaylistMode,
+    #[serde(default = "default_transition")]
+    transition: f32,
+    blend: Option<String>,
+    seed: Option<u64>,
+    effects: Vec<EntryFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EntryFile {
+    effect: String,
+    duration: f32,
+    #[serde(default = "default_weight")]
+    weight: f32,
+    #[serde(default)]
+    params: Table,
+}
+
+fn default_transition() -> f32 {
+    1.0
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// Cycles through a list of effects unattended, crossfading between them.
+///
+/// ```toml
+/// mode = "shuffle" # sequential, shuffle or weighted
+/// transition = 2.0
+/// blend = "oklab"
+/// seed = 7 # optional, makes the order and every effect repeat exactly
+///
+/// [[effects]]
+/// effect = "ripples"
+/// duration = 90
+///
+/// [[effects]]
+/// effect = "embers"
+/// duration = 120
+/// weight = 2.0
+/// params = { time_scale = 0.4 }
+/// ```
+pub struct Playlist {
+    pub mode: PlaylistMode,
+    pub transition: Duration,
+    pub blend: Blend,
+    entries: Vec<PlaylistEntry>,
+    order: Vec<usize>,
+    position: usize,
+    rng: StdRng,
+}
+
+impl Playlist {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SledError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|e| SledError::new(format!("Failed to read `{}`: {}", path.display(), e)))?;
+        Self::parse(&source)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, SledError> {
+        let file: PlaylistFile = toml::from_str(source).map_err(SledError::from_error)?;
+
+        if file.effects.is_empty() {
+            return SledError::from("A playlist needs at least one effect.").as_err();
+        }
+
+        let mut entries = vec![];
+        for entry in file.effects {
+            let effect = registry::find(&entry.effect).ok_or_else(|| {
+                SledError::new(format!("Unknown effect `{}` in playlist.", entry.effect))
+            })?;
+
+            if !(entry.duration > 0.0 && entry.duration.is_finite()) {
+                return SledError::new(format!(
+                    "`{}` needs a positive duration in seconds.",
+                    effect.name
+                ))
+                .as_err();
+            }
+
+            if !(entry.weight >= 0.0 && entry.weight.is_finite()) {
+                return SledError::new(format!("`{}` has an invalid weight.", effect.name))
+                    .as_err();
+            }
+
+            // catch bad presets now rather than minutes into a show
+            params::apply_table(&entry.params, effect.name, effect.params, &mut Data::new())?;
+
+            entries.push(PlaylistEntry {
+                effect,
+                duration: Duration::from_secs_f32(entry.duration),
+                weight: entry.weight,
+                params: entry.params,
+            });
+        }
+
+        if file.mode == PlaylistMode::Weighted && entries.iter().all(|e| e.weight == 0.0) {
+            return SledError::from("A weighted playlist needs at least one non-zero weight.")
+                .as_err();
+        }
+
+        let blend = match file.blend {
+            Some(blend) => blend.parse().map_err(SledError::new)?,
+            None => Blend::default(),
+        };
+
+        let mut playlist = Playlist {
+            mode: file.mode,
+            transition: Duration::from_secs_f32(file.transition.max(0.0)),
+            blend,
+            order: (0..entries.len()).collect(),
+            entries,
+            position: 0,
+            rng: match file.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+        };
+        playlist.reorder();
+        Ok(playlist)
+    }
+
+    /// Restarts the playlist from the top with a new seed, replacing any in the file.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.order = (0..self.entries.len()).collect();
+        self.position = 0;
+        self.reorder();
+    }
+
+    pub fn entries(&self) -> &[PlaylistEntry] {
+        &self.entries
+    }
+
+    pub fn current(&self) -> &PlaylistEntry {
+        &self.entries[self.order[self.position]]
+    }
+
+    /// Mounts the first entry on `sled`, ready to be played with `update`.
+    pub fn start(&mut self, sled: Sled<Rgb>, base: &ParamFile) -> Result<Crossfader, SledError> {
+        let driver = self.build_current(base)?;
+        Ok(Crossfader::new(sled, driver, self.blend, self.transition))
+    }
+
+    // each play of an entry gets a fresh seed drawn from the playlist's own rng, so
+    // entries don't repeat themselves but a seeded playlist still plays out identically
+    fn build_current(&mut self, base: &ParamFile) -> Result<Driver<Rgb>, SledError> {
+        let mut driver = self.current().build_driver(base)?;
+        rng::set_seed(driver.data_mut(), self.rng.gen());
+        Ok(driver)
+    }
+
+    /// Moves on to the next entry once the current one has played for its duration.
+    /// Returns the new entry whenever it switched.
+    pub fn update(
+        &mut self,
+        fader: &mut Crossfader,
+        base: &ParamFile,
+    ) -> Result<Option<&PlaylistEntry>, SledError> {
+        if fader.target().elapsed() < self.current().duration {
+            return Ok(None);
+        }
+
+        self.advance();
+        fader.set_blend(self.blend);
+        fader.set_duration(self.transition);
+        let driver = self.build_current(base)?;
+        fader.switch_to(driver);
+        Ok(Some(self.current()))
+    }
+
+    pub fn advance(&mut self) -> &PlaylistEntry {
+        match self.mode {
+            PlaylistMode::Sequential | PlaylistMode::Shuffle => {
+                self.position += 1;
+                if self.position >= self.order.len() {
+                    self.position = 0;
+                    self.reorder();
+                }
+            }
+            PlaylistMode::Weighted => {
+                // never pick the same entry twice in a row unless it's the only option
+                let last = self.order[self.position];
+                let weights = self.entries.iter().enumerate().map(|(i, entry)| match i {
+                    i if i == last => 0.0,
+                    _ => entry.weight,
+                });
+
+                if let Ok(distribution) = WeightedIndex::new(weights) {
+                    self.order[self.position] = distribution.sample(&mut self.rng);
+                }
+            }
+        }
+        self.current()
+    }
+
+    fn reorder(&mut self) {
+        match self.mode {
+            PlaylistMode::Sequential => {}
+            PlaylistMode::Shuffle => {
+                let last = self.order.last().copied();
+                self.order.shuffle(&mut self.rng);
+                // avoid playing the same effect back to back across reshuffles
+                if self.order.len() > 1 && self.order.first().copied() == last {
+                    self.order.swap(0, 1);
+                }
+            }
+            PlaylistMode::Weighted => {
+                let weights = self.entries.iter().map(|entry| entry.weight);
+                let distribution = WeightedIndex::new(weights).unwrap();
+                self.order = vec![distribution.sample(&mut self.rng)];
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::{fmt, str::FromStr};
+
+use palette::{rgb::Rgb, IntoColor, Oklch};
+use serde::Deserialize;
+use spatial_led::SledError;
+use toml::{Table, Value};
+
+use crate::params::FromTable;
+
+/// Squeezes colors brighter than 1 back into range, instead of letting them clip.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tonemap {
+    /// Leaves colors alone; anything above 1 clips.
+    #[default]
+    None,
+    /// `c / (1 + c)` per channel. Gentle, but dims everything a little.
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve, with more contrast than Reinhard.
+    Aces,
+}
+
+impl Tonemap {
+    pub fn map(self, c: f32) -> f32 {
+        match self {
+            Tonemap::None => c,
+            Tonemap::Reinhard => c / (1.0 + c),
+            Tonemap::Aces => {
+                let c = c.max(0.0);
+                ((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14)).min(1.0)
+            }
+        }
+    }
+}
+
+impl FromStr for Tonemap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Tonemap::None),
+            "reinhard" => Ok(Tonemap::Reinhard),
+            "aces" => Ok(Tonemap::Aces),
+            _ => Err(format!(
+                "Unknown tonemap `{}`. Expected none, reinhard or aces.",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Tonemap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tonemap::None => write!(f, "none"),
+            Tonemap::Reinhard => write!(f, "reinhard"),
+            Tonemap::Aces => write!(f, "aces"),
+        }
+    }
+}
+
+/// Adjustments applied to every frame after the effects have drawn it, in the order
+/// the fields are listed. The result is always clamped to `0..=1`.
+///
+/// Loaded from the `[post]` table of a params file:
+///
+/// ```toml
+/// [post]
+/// tonemap = "aces"  # none, reinhard or aces
+/// saturation = 1.2  # 0 is grayscale
+/// hue_shift = 30.0  # degrees
+/// brightness = 0.6
+/// ```
+///
+/// Gamma isn't set here: frames stay linear for the terminal preview, and hardware
+/// outputs gamma correct them through the `[output]` table.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PostProcess {
+    pub tonemap: Tonemap,
+    pub saturation: f32,
+    pub hue_shift: f32,
+    pub brightness: f32,
+}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        PostProcess {
+            tonemap: Tonemap::None,
+            saturation: 1.0,
+            hue_shift: 0.0,
+            brightness: 1.0,
+        }
+    }
+}
+
+impl FromTable for PostProcess {
+    fn from_table(table: &Table) -> Result<Self, SledError> {
+        if table.contains_key("gamma") {
+            return SledError::new(
+                "Set gamma in [output], which applies it to hardware only, not in [post].".into(),
+            )
+            .as_err();
+        }
+        let post: PostProcess = Value::Table(table.clone())
+            .try_into()
+            .map_err(|e| SledError::new(format!("Invalid [post] table: {}", e)))?;
+
+        let checks = [
+            ("saturation", post.saturation, post.saturation >= 0.0),
+            ("hue_shift", post.hue_shift, post.hue_shift.is_finite()),
+            ("brightness", post.brightness, post.brightness >= 0.0),
+        ];
+        for (name, value, ok) in checks {
+            if !(ok && value.is_finite()) {
+                return SledError::new(format!("`{}` can't be {} in [post].", name, value))
+                    .as_err();
+            }
+        }
+        Ok(post)
+    }
+}
+
+impl PostProcess {
+    pub fn apply(&self, colors: &mut [Rgb]) {
+        for color in colors {
+            *color = self.process(*color);
+        }
+    }
+
+    pub fn process(&self, color: Rgb) -> Rgb {
+        let map = |c: f32| self.tonemap.map(c);
+        let mut color: Rgb = Rgb::new(map(color.red), map(color.green), map(color.blue));
+
+        if self.saturation != 1.0 || self.hue_shift != 0.0 {
+            let mut lch: Oklch = color.into_color();
+            lch.chroma *= self.saturation;
+            lch.hue += self.hue_shift;
+            color = lch.into_color();
+        }
+
+        let finish = |c: f32| (c * self.brightness).clamp(0.0, 1.0);
+        Rgb::new(finish(color.red), finish(color.green), finish(color.blue))
+    }
+}
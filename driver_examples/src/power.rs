@@ -0,0 +1,224 @@
+use std::ops::Range;
+
+use palette::rgb::Rgb;
+use serde::Deserialize;
+use spatial_led::{Sled, SledError};
+use toml::{Table, Value};
+
+use crate::params::FromTable;
+
+/// A separately powered stretch of the strip, fed by its own injection point.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Injection {
+    /// Segments of the room layout this point powers.
+    #[serde(default)]
+    pub segments: Vec<usize>,
+    /// LED indices this point powers, as `[start, end]` with `end` excluded.
+    pub leds: Option<[usize; 2]>,
+    /// The most current, in milliamps, that may flow through this point.
+    pub budget_ma: f32,
+}
+
+/// How much current the strip draws, and how much it's allowed to.
+///
+/// Loaded from the `[power]` table of a params file:
+///
+/// ```toml
+/// [power]
+/// ma_per_channel = 20.0 # each channel at full brightness
+/// idle_ma = 1.0         # each led, even when dark
+/// budget_ma = 10000.0   # the supply's limit, leave it out to only estimate
+///
+/// [[power.injections]]
+/// segments = [0, 1]
+/// budget_ma = 3000.0
+///
+/// [[power.injections]]
+/// leds = [600, 1200]
+/// budget_ma = 3000.0
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerConfig {
+    pub ma_per_channel: f32,
+    pub idle_ma: f32,
+    pub budget_ma: Option<f32>,
+    pub injections: Vec<Injection>,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        PowerConfig {
+            ma_per_channel: 20.0,
+            idle_ma: 1.0,
+            budget_ma: None,
+            injections: vec![],
+        }
+    }
+}
+
+impl FromTable for PowerConfig {
+    fn from_table(table: &Table) -> Result<Self, SledError> {
+        let config: PowerConfig = Value::Table(table.clone())
+            .try_into()
+            .map_err(|e| SledError::new(format!("Invalid [power] table: {}", e)))?;
+
+        let budgets = config.injections.iter().map(|i| i.budget_ma);
+        let values = [config.ma_per_channel, config.idle_ma]
+            .into_iter()
+            .chain(config.budget_ma)
+            .chain(budgets);
+        for value in values {
+            if !(value >= 0.0 && value.is_finite()) {
+                return SledError::new(format!("[power] can't use {} milliamps.", value)).as_err();
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// A frame's current draw, in milliamps, before and after limiting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerEstimate {
+    pub requested_ma: f32,
+    pub output_ma: f32,
+    /// How much the brightest limited part of the frame was dimmed by, 1 if not at all.
+    pub scale: f32,
+}
+
+impl Default for PowerEstimate {
+    fn default() -> Self {
+        PowerEstimate {
+            requested_ma: 0.0,
+            output_ma: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+/// Dims frames that would draw more current than the supply or any injection point
+/// can provide.
+///
+/// Estimates assume each channel's draw is linear in its value, so they run high for
+/// outputs that gamma correct afterwards. That errs on the safe side.
+#[derive(Clone, Debug)]
+pub struct PowerLimiter {
+    config: PowerConfig,
+    zones: Vec<(Vec<usize>, f32)>,
+    estimate: PowerEstimate,
+}
+
+impl PowerLimiter {
+    pub fn new(config: PowerConfig, sled: &Sled<Rgb>) -> Result<Self, SledError> {
+        let mut zones = vec![];
+        for injection in &config.injections {
+            if let Some(&segment) = injection
+                .segments
+                .iter()
+                .find(|&&s| s >= sled.num_segments())
+            {
+                return SledError::new(format!(
+                    "Injection point powers segment {}, but the room only has {}.",
+                    segment,
+                    sled.num_segments()
+                ))
+                .as_err();
+            }
+
+            let range: Range<usize> = match injection.leds {
+                Some([start, end]) if start <= end && end <= sled.num_leds() => start..end,
+                Some([start, end]) => {
+                    return SledError::new(format!(
+                        "Injection point powers leds {}..{}, but the room has {}.",
+                        start,
+                        end,
+                        sled.num_leds()
+                    ))
+                    .as_err()
+                }
+                None => 0..0,
+            };
+
+            let leds = sled
+                .leds()
+                .filter(|led| {
+                    let index = led.index() as usize;
+                    range.contains(&index) || injection.segments.contains(&(led.segment() as usize))
+                })
+                .map(|led| led.index() as usize)
+                .collect();
+            zones.push((leds, injection.budget_ma));
+        }
+
+        Ok(PowerLimiter {
+            config,
+            zones,
+            estimate: PowerEstimate::default(),
+        })
+    }
+
+    pub fn config(&self) -> &PowerConfig {
+        &self.config
+    }
+
+    /// Milliamps a single LED draws showing `color`.
+    pub fn led_ma(&self, color: Rgb) -> f32 {
+        let channels = color.red.max(0.0) + color.green.max(0.0) + color.blue.max(0.0);
+        self.config.idle_ma + channels * self.config.ma_per_channel
+    }
+
+    pub fn estimate_ma(&self, colors: &[Rgb]) -> f32 {
+        colors.iter().map(|c| self.led_ma(*c)).sum()
+    }
+
+    /// Scales `colors` down until they fit every budget, returning the estimate, which
+    /// is also kept around for `last_estimate`.
+    pub fn apply(&mut self, colors: &mut [Rgb]) -> PowerEstimate {
+        let requested_ma = self.estimate_ma(colors);
+        let mut scale: f32 = 1.0;
+
+        for (leds, budget) in &self.zones {
+            let draw = leds.iter().filter_map(|&i| colors.get(i));
+            let zone_scale = self.fit(draw, leds.len(), *budget);
+            if zone_scale < 1.0 {
+                for &i in leds {
+                    if let Some(color) = colors.get_mut(i) {
+                        *color *= zone_scale;
+                    }
+                }
+            }
+            scale = scale.min(zone_scale);
+        }
+
+        if let Some(budget) = self.config.budget_ma {
+            let total_scale = self.fit(colors.iter(), colors.len(), budget);
+            if total_scale < 1.0 {
+                colors.iter_mut().for_each(|color| *color *= total_scale);
+            }
+            scale = scale.min(total_scale);
+        }
+
+        self.estimate = PowerEstimate {
+            requested_ma,
+            output_ma: self.estimate_ma(colors),
+            scale,
+        };
+        self.estimate
+    }
+
+    pub fn last_estimate(&self) -> PowerEstimate {
+        self.estimate
+    }
+
+    // how much the lit part of `colors` has to be scaled by for all of them to draw
+    // no more than `budget`, given the idle draw can't be scaled away
+    fn fit<'a>(&self, colors: impl Iterator<Item = &'a Rgb>, count: usize, budget: f32) -> f32 {
+        let idle = count as f32 * self.config.idle_ma;
+        let lit: f32 = colors.map(|c| self.led_ma(*c) - self.config.idle_ma).sum();
+        if idle + lit <= budget || lit <= 0.0 {
+            return 1.0;
+        }
+        ((budget - idle) / lit).clamp(0.0, 1.0)
+    }
+}
@@ -0,0 +1,78 @@
+use palette::rgb::Rgb;
+use spatial_led::SledError;
+
+use crate::clock::Driver;
+use crate::effects::{comet, embers, ripples, scan, warpspeed};
+use crate::params::{ParamFile, ParamSpec};
+
+/// Describes an effect so that front ends can list and build it by name.
+#[derive(Debug)]
+pub struct EffectInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub credit: Option<&'static str>,
+    pub params: &'static [ParamSpec],
+    build: fn() -> Driver<Rgb>,
+}
+
+impl EffectInfo {
+    pub fn build_driver(&self) -> Driver<Rgb> {
+        (self.build)()
+    }
+
+    /// Builds the effect with any parameters `params` lists for it in place of the defaults.
+    pub fn build_driver_with(&self, params: &ParamFile) -> Result<Driver<Rgb>, SledError> {
+        let mut driver = self.build_driver();
+        params.apply(self.name, self.params, driver.data_mut())?;
+        Ok(driver)
+    }
+}
+
+pub const EFFECTS: &[EffectInfo] = &[
+    EffectInfo {
+        name: "comet",
+        description: "Green and blue points orbiting the room with a sweeping trail. Mostly used as a benchmark.",
+        credit: None,
+        params: comet::PARAMS,
+        build: comet::build_driver,
+    },
+    EffectInfo {
+        name: "embers",
+        description: "Rigid multi-fractal noise drifting across the room, mapped onto a color ramp.",
+        credit: Some("Midnight Ablaze palette by Inkpendude (https://lospec.com/palette-list/midnight-ablaze)"),
+        params: embers::PARAMS,
+        build: embers::build_driver,
+    },
+    EffectInfo {
+        name: "ripples",
+        description: "Growing rings of color at random points in the room.",
+        credit: None,
+        params: ripples::PARAMS,
+        build: ripples::build_driver,
+    },
+    EffectInfo {
+        name: "scan",
+        description: "A line sweeping through the room at random angles, cycling through the rainbow.",
+        credit: None,
+        params: scan::PARAMS,
+        build: scan::build_driver,
+    },
+    EffectInfo {
+        name: "warpspeed",
+        description: "Stars zooming past the center point as if traveling at light speed.",
+        credit: None,
+        params: warpspeed::PARAMS,
+        build: warpspeed::build_driver,
+    },
+];
+
+/// Looks up an effect by name, ignoring case.
+pub fn find(name: &str) -> Option<&'static EffectInfo> {
+    EFFECTS
+        .iter()
+        .find(|effect| effect.name.eq_ignore_ascii_case(name))
+}
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    EFFECTS.iter().map(|effect| effect.name)
+}
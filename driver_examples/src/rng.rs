@@ -0,0 +1,35 @@
+use rand::{rngs::StdRng, SeedableRng};
+use spatial_led::{driver::Data, SledError, SledResult};
+
+/// Where a driver's seed is kept in its `Data`, as a `u64`.
+pub const SEED: &str = "seed";
+/// Where a driver's random number generator is kept in its `Data`, as a `StdRng`.
+pub const RNG: &str = "rng";
+
+/// Seeds a driver before it's mounted, so it plays out the same way every time.
+pub fn set_seed(data: &mut Data, seed: u64) {
+    data.set(SEED, seed);
+}
+
+/// Creates the driver's random number generator from its seed, picking a random seed
+/// first if none was set. Effects call this during startup.
+///
+/// Returns a copy of the generator, like `take`.
+pub fn init(data: &mut Data) -> Result<StdRng, SledError> {
+    if data.empty_at(SEED) {
+        data.set(SEED, rand::random::<u64>());
+    }
+    let seed = *data.get::<u64>(SEED)?;
+    Ok(data.store(RNG, StdRng::seed_from_u64(seed)).clone())
+}
+
+/// Copies the driver's random number generator out of `data`, so it can be used while
+/// other values in `data` are borrowed. Hand it back with `store` once done.
+pub fn take(data: &Data) -> Result<StdRng, SledError> {
+    data.get::<StdRng>(RNG).cloned()
+}
+
+pub fn store(data: &mut Data, rng: StdRng) -> SledResult {
+    *data.get_mut::<StdRng>(RNG)? = rng;
+    Ok(())
+}
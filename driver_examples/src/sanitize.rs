@@ -0,0 +1,113 @@
+use std::{fmt, str::FromStr};
+
+use palette::rgb::Rgb;
+use spatial_led::{SledError, SledResult};
+
+/// What to do with colors that aren't finite and non-negative.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SanitizeMode {
+    /// Fixes each bad channel on its own: NaN and negative channels become 0 and
+    /// infinite ones become 1.
+    #[default]
+    Clamp,
+    /// Replaces the whole LED with the sanitizer's replacement color.
+    Replace,
+    /// Fails with the index of the first bad LED, for tracking down the effect at fault.
+    Error,
+}
+
+impl FromStr for SanitizeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "clamp" => Ok(SanitizeMode::Clamp),
+            "replace" => Ok(SanitizeMode::Replace),
+            "error" => Ok(SanitizeMode::Error),
+            _ => Err(format!(
+                "Unknown sanitize mode `{}`. Expected clamp, replace or error.",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SanitizeMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanitizeMode::Clamp => write!(f, "clamp"),
+            SanitizeMode::Replace => write!(f, "replace"),
+            SanitizeMode::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Catches NaN, infinite and negative colors in each finished frame. It runs once the
+/// crossfader has blended the outgoing and incoming effects, which carries a bad color
+/// through unchanged, and before post-processing, power limiting and the outputs.
+///
+/// Colors brighter than 1 are left alone, since additive effects rely on them; `[post]`
+/// tonemaps or clamps them into range afterwards.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sanitizer {
+    pub mode: SanitizeMode,
+    /// Used in place of bad LEDs by `SanitizeMode::Replace`.
+    pub replacement: Rgb,
+}
+
+impl Default for Sanitizer {
+    fn default() -> Self {
+        Sanitizer::new(SanitizeMode::default())
+    }
+}
+
+impl Sanitizer {
+    pub fn new(mode: SanitizeMode) -> Self {
+        Sanitizer {
+            mode,
+            replacement: Rgb::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn apply(&self, colors: &mut [Rgb]) -> SledResult {
+        for (i, color) in colors.iter_mut().enumerate() {
+            if is_valid(*color) {
+                continue;
+            }
+
+            match self.mode {
+                SanitizeMode::Clamp => {
+                    *color = Rgb::new(
+                        clamp_channel(color.red),
+                        clamp_channel(color.green),
+                        clamp_channel(color.blue),
+                    )
+                }
+                SanitizeMode::Replace => *color = self.replacement,
+                SanitizeMode::Error => {
+                    return SledError::new(format!(
+                        "LED {} has an invalid color ({}, {}, {}).",
+                        i, color.red, color.green, color.blue
+                    ))
+                    .as_err()
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// True if every channel is finite and non-negative.
+pub fn is_valid(color: Rgb) -> bool {
+    [color.red, color.green, color.blue]
+        .iter()
+        .all(|c| c.is_finite() && *c >= 0.0)
+}
+
+fn clamp_channel(c: f32) -> f32 {
+    match c {
+        c if c.is_nan() => 0.0,
+        c if c == f32::INFINITY => 1.0,
+        c => c.max(0.0),
+    }
+}
@@ -0,0 +1,189 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufWriter, Write},
+    path::Path,
+};
+
+use palette::rgb::Rgb;
+use spatial_led::{SledError, SledResult, Vec2};
+
+const RECORDING_HEADER: &str = "sled-recording 1";
+
+/// A finished frame: every LED's color, along with where it sits in the room.
+#[derive(Clone, Copy, Debug)]
+pub struct Frame<'a> {
+    pub colors: &'a [Rgb],
+    pub positions: &'a [Vec2],
+}
+
+impl<'a> Frame<'a> {
+    pub fn new(colors: &'a [Rgb], positions: &'a [Vec2]) -> Self {
+        Frame { colors, positions }
+    }
+
+    pub fn colors_and_positions(&self) -> impl Iterator<Item = (Rgb, Vec2)> + 'a {
+        self.colors
+            .iter()
+            .copied()
+            .zip(self.positions.iter().copied())
+    }
+}
+
+/// Somewhere finished frames are sent: a strip, the terminal, the network or a file.
+pub trait LedSink {
+    fn send(&mut self, frame: &Frame) -> SledResult;
+}
+
+impl<S: LedSink + ?Sized> LedSink for Box<S> {
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        (**self).send(frame)
+    }
+}
+
+/// Sends every frame to each of its sinks, in the order they were added.
+#[derive(Default)]
+pub struct FanOut {
+    sinks: Vec<Box<dyn LedSink>>,
+}
+
+impl FanOut {
+    pub fn new() -> Self {
+        FanOut::default()
+    }
+
+    pub fn with_sink(mut self, sink: impl LedSink + 'static) -> Self {
+        self.push(sink);
+        self
+    }
+
+    pub fn push(&mut self, sink: impl LedSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+}
+
+impl LedSink for FanOut {
+    /// Every sink gets the frame even if an earlier one failed; the first error is
+    /// returned afterwards.
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        let mut result = Ok(());
+        for sink in &mut self.sinks {
+            let sent = sink.send(frame);
+            if result.is_ok() {
+                result = sent;
+            }
+        }
+        result
+    }
+}
+
+/// Writes frames to a text file, one line of `rrggbb` hex colors per frame, after a
+/// header giving the LED count. Colors are clamped and rounded to 8 bits as they are,
+/// without any gamma correction.
+pub struct Recorder<W: Write> {
+    writer: W,
+    leds: Option<usize>,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, SledError> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|e| SledError::new(format!("Failed to create `{}`: {}", path.display(), e)))?;
+        Ok(Recorder::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(writer: W) -> Self {
+        Recorder { writer, leds: None }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> LedSink for Recorder<W> {
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        // the header goes out with the first frame, once the LED count is known
+        let first = self.leds.is_none();
+        let leds = *self.leds.get_or_insert(frame.colors.len());
+        if leds != frame.colors.len() {
+            return SledError::new(format!(
+                "Recording has {} LEDs, but the frame has {}.",
+                leds,
+                frame.colors.len()
+            ))
+            .as_err();
+        }
+
+        let mut line = String::with_capacity(leds * 7 + 1);
+        if first {
+            line.push_str(&format!("{} {}\n", RECORDING_HEADER, leds));
+        }
+        for (i, color) in frame.colors.iter().enumerate() {
+            let [r, g, b] = [color.red, color.green, color.blue].map(to_byte);
+            if i > 0 {
+                line.push(' ');
+            }
+            line.push_str(&format!("{:02x}{:02x}{:02x}", r, g, b));
+        }
+        line.push('\n');
+
+        self.writer
+            .write_all(line.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .map_err(SledError::from_error)
+    }
+}
+
+fn to_byte(c: f32) -> u8 {
+    // NaN casts to 0
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Reads back the frames written by a `Recorder`.
+pub fn read_recording(reader: impl BufRead) -> Result<Vec<Vec<[u8; 3]>>, SledError> {
+    let mut lines = reader.lines();
+    let header = lines
+        .next()
+        .transpose()
+        .map_err(SledError::from_error)?
+        .unwrap_or_default();
+    let leds: usize = header
+        .strip_prefix(RECORDING_HEADER)
+        .and_then(|count| count.trim().parse().ok())
+        .ok_or_else(|| SledError::from("Not a recording, the header is missing."))?;
+
+    let mut frames = vec![];
+    for (i, line) in lines.enumerate() {
+        let line = line.map_err(SledError::from_error)?;
+        let frame =
+            line.split_whitespace()
+                .map(|hex| match (hex.len(), u32::from_str_radix(hex, 16)) {
+                    (6, Ok(value)) => Ok([(value >> 16) as u8, (value >> 8) as u8, value as u8]),
+                    _ => SledError::new(format!("Frame {} has an invalid color `{}`.", i, hex))
+                        .as_err(),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+        if frame.len() != leds {
+            return SledError::new(format!(
+                "Frame {} has {} LEDs, expected {}.",
+                i,
+                frame.len(),
+                leds
+            ))
+            .as_err();
+        }
+        frames.push(frame);
+    }
+    Ok(frames)
+}
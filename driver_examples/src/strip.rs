@@ -0,0 +1,141 @@
+use palette::rgb::Rgb;
+use serde::Deserialize;
+use spatial_led::SledError;
+use toml::{Table, Value};
+
+use crate::params::FromTable;
+
+/// The LED chip family a strip is made of.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StripType {
+    #[default]
+    Ws2811,
+    Ws2812,
+    Sk6812,
+    /// SK6812 with a fourth, white LED in every pixel.
+    Sk6812w,
+}
+
+impl StripType {
+    pub fn has_white(self) -> bool {
+        self == StripType::Sk6812w
+    }
+}
+
+/// The order a strip expects its color channels in. RGBW strips always take white last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorOrder {
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+}
+
+/// The physical strip being driven.
+///
+/// Loaded from the `[strip]` table of a params file:
+///
+/// ```toml
+/// [strip]
+/// type = "sk6812w"          # ws2811, ws2812, sk6812 or sk6812w
+/// order = "grb"             # rgb, rbg, grb, gbr, brg or bgr
+/// white_temperature = 4000  # kelvin, for sk6812w strips
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StripConfig {
+    #[serde(rename = "type")]
+    pub strip_type: StripType,
+    pub order: Option<ColorOrder>,
+    pub white_temperature: f32,
+}
+
+impl Default for StripConfig {
+    fn default() -> Self {
+        StripConfig {
+            strip_type: StripType::default(),
+            order: None,
+            white_temperature: 4000.0,
+        }
+    }
+}
+
+impl FromTable for StripConfig {
+    fn from_table(table: &Table) -> Result<Self, SledError> {
+        let config: StripConfig = Value::Table(table.clone())
+            .try_into()
+            .map_err(|e| SledError::new(format!("Invalid [strip] table: {}", e)))?;
+
+        if !(1000.0..=40_000.0).contains(&config.white_temperature) {
+            return SledError::new(format!(
+                "[strip] white_temperature should be between 1000 and 40000 kelvin, found {}.",
+                config.white_temperature
+            ))
+            .as_err();
+        }
+        Ok(config)
+    }
+}
+
+impl StripConfig {
+    /// The configured color order, or GRB, which every supported chip uses by default.
+    pub fn order(&self) -> ColorOrder {
+        self.order.unwrap_or(ColorOrder::Grb)
+    }
+
+    /// The color of the white LED for RGBW strips, brightest channel at 1.
+    pub fn white(&self) -> Option<Rgb> {
+        match self.strip_type.has_white() {
+            true => Some(kelvin_to_rgb(self.white_temperature)),
+            false => None,
+        }
+    }
+}
+
+/// Approximates the color of a black body at `kelvin`, scaled so its brightest
+/// channel is 1. Follows Tanner Helland's fit, good from 1000 to 40000 kelvin.
+pub fn kelvin_to_rgb(kelvin: f32) -> Rgb {
+    let t = kelvin.clamp(1000.0, 40_000.0) / 100.0;
+
+    let red = match t <= 66.0 {
+        true => 255.0,
+        false => 329.699 * (t - 60.0).powf(-0.133_205),
+    };
+    let green = match t <= 66.0 {
+        true => 99.470_8 * t.ln() - 161.119_57,
+        false => 288.122_16 * (t - 60.0).powf(-0.075_514_85),
+    };
+    let blue = match t {
+        t if t >= 66.0 => 255.0,
+        t if t <= 19.0 => 0.0,
+        t => 138.517_73 * (t - 10.0).ln() - 305.044_8,
+    };
+
+    let [red, green, blue] = [red, green, blue].map(|c: f32| c.clamp(0.0, 255.0));
+    let max = red.max(green).max(blue);
+    Rgb::new(red / max, green / max, blue / max)
+}
+
+/// Moves as much of `color` as possible onto a white LED of color `white`, returning
+/// what's left for the RGB LEDs along with the white LED's level.
+pub fn extract_white(color: Rgb, white: Rgb) -> (Rgb, f32) {
+    let level = |c: f32, w: f32| match w > 0.0 {
+        true => c.max(0.0) / w,
+        false => f32::INFINITY,
+    };
+    let w = level(color.red, white.red)
+        .min(level(color.green, white.green))
+        .min(level(color.blue, white.blue))
+        .min(1.0);
+
+    let rest = Rgb::new(
+        (color.red - white.red * w).max(0.0),
+        (color.green - white.green * w).max(0.0),
+        (color.blue - white.blue * w).max(0.0),
+    );
+    (rest, w)
+}
@@ -0,0 +1,169 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use palette::{rgb::Rgb, IntoColor, Mix, Oklab};
+use spatial_led::{Sled, Vec2};
+
+use crate::clock::Driver;
+
+/// How the outgoing and incoming effects are mixed during a transition.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Blend {
+    /// Interpolates each channel directly.
+    #[default]
+    Linear,
+    /// Interpolates in Oklab, keeping perceived brightness steadier mid-fade.
+    Oklab,
+    /// Fades the old effect out completely before fading the new one in.
+    DipToBlack,
+}
+
+impl Blend {
+    pub fn mix(self, from: Rgb, to: Rgb, t: f32) -> Rgb {
+        match self {
+            Blend::Linear => from * (1.0 - t) + to * t,
+            Blend::Oklab => {
+                let from: Oklab = from.into_color();
+                let to: Oklab = to.into_color();
+                from.mix(to, t).into_color()
+            }
+            Blend::DipToBlack => match t < 0.5 {
+                true => from * (1.0 - t * 2.0),
+                false => to * (t * 2.0 - 1.0),
+            },
+        }
+    }
+}
+
+impl FromStr for Blend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "linear" => Ok(Blend::Linear),
+            "oklab" => Ok(Blend::Oklab),
+            "dip" | "dip-to-black" => Ok(Blend::DipToBlack),
+            _ => Err(format!(
+                "Unknown blend `{}`. Expected linear, oklab or dip-to-black.",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Blend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Blend::Linear => write!(f, "linear"),
+            Blend::Oklab => write!(f, "oklab"),
+            Blend::DipToBlack => write!(f, "dip-to-black"),
+        }
+    }
+}
+
+/// Plays one driver at a time on a room layout, crossfading whenever it's told to
+/// switch to another.
+///
+/// Each driver is mounted on its own copy of the layout, so the outgoing effect keeps
+/// animating underneath the incoming one until the blend completes.
+pub struct Crossfader {
+    sled: Sled<Rgb>,
+    current: Driver<Rgb>,
+    incoming: Option<Driver<Rgb>>,
+    blend: Blend,
+    duration: Duration,
+    output: Vec<Rgb>,
+}
+
+impl Crossfader {
+    pub fn new(sled: Sled<Rgb>, mut driver: Driver<Rgb>, blend: Blend, duration: Duration) -> Self {
+        driver.mount(sled.clone());
+        let output = vec![Rgb::default(); sled.num_leds()];
+        Crossfader {
+            sled,
+            current: driver,
+            incoming: None,
+            blend,
+            duration,
+            output,
+        }
+    }
+
+    pub fn set_blend(&mut self, blend: Blend) {
+        self.blend = blend;
+    }
+
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    /// Starts fading into `driver`. If a transition is already underway, its incoming
+    /// driver is promoted to the current one first.
+    pub fn switch_to(&mut self, mut driver: Driver<Rgb>) {
+        driver.mount(self.sled.clone());
+        if let Some(previous) = self.incoming.replace(driver) {
+            self.current = previous;
+        }
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.incoming.is_some()
+    }
+
+    /// The driver being faded into, or the current one if nothing is transitioning.
+    /// This is the driver whose parameters a front end should be editing.
+    pub fn target(&self) -> &Driver<Rgb> {
+        self.incoming.as_ref().unwrap_or(&self.current)
+    }
+
+    pub fn target_mut(&mut self) -> &mut Driver<Rgb> {
+        self.incoming.as_mut().unwrap_or(&mut self.current)
+    }
+
+    pub fn step(&mut self) {
+        self.current.step();
+
+        let Some(incoming) = &mut self.incoming else {
+            for (out, color) in self.output.iter_mut().zip(self.current.colors()) {
+                *out = *color;
+            }
+            return;
+        };
+
+        incoming.step();
+        let t = match self.duration.is_zero() {
+            true => 1.0,
+            false => (incoming.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0),
+        };
+
+        let colors = self.current.colors().zip(incoming.colors());
+        for (out, (from, to)) in self.output.iter_mut().zip(colors) {
+            *out = self.blend.mix(*from, *to, t);
+        }
+
+        if t >= 1.0 {
+            self.current = self.incoming.take().unwrap();
+        }
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &Rgb> + '_ {
+        self.output.iter()
+    }
+
+    /// The blended output as one slice, for handing whole frames to outputs.
+    pub fn output(&self) -> &[Rgb] {
+        &self.output
+    }
+
+    /// The blended output, for stages that adjust it in place after each step.
+    pub fn colors_mut(&mut self) -> &mut [Rgb] {
+        &mut self.output
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.sled.positions()
+    }
+
+    pub fn colors_and_positions(&self) -> impl Iterator<Item = (Rgb, Vec2)> + '_ {
+        self.output.iter().copied().zip(self.sled.positions())
+    }
+}
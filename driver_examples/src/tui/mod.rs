@@ -0,0 +1,228 @@
+use crossterm::{
+    event::{self, Event, KeyCode},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+
+use ratatui::{
+    prelude::*,
+    widgets::{
+        canvas::{Canvas, Circle, Context},
+        *,
+    },
+};
+
+use palette::rgb::Rgb;
+use spatial_led::{driver::Data, SledError, SledResult, Vec2};
+
+use std::{
+    io::{self, stdout, Error, Stdout},
+    ops::Range,
+    time::Duration,
+};
+
+use crate::{
+    params::ParamSpec,
+    sink::{Frame, LedSink},
+};
+
+mod params_panel;
+use params_panel::ParamsPanel;
+
+pub struct SledTerminalDisplay {
+    title: String,
+    leds: Vec<(Rgb, Vec2)>,
+    on_quit: Box<dyn FnMut()>,
+    quit: bool,
+    stopped: bool,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+    params_panel: Option<ParamsPanel>,
+    effect_request: Option<usize>,
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl SledTerminalDisplay {
+    pub fn start(title: &str, domain: Range<Vec2>) -> Self {
+        enable_raw_mode().unwrap();
+        stdout().execute(EnterAlternateScreen).unwrap();
+        SledTerminalDisplay {
+            title: String::from(title),
+            leds: vec![],
+            x_bounds: [domain.start.x as f64, domain.end.x as f64],
+            y_bounds: [domain.start.y as f64, domain.end.y as f64],
+            params_panel: None,
+            effect_request: None,
+            terminal: Terminal::new(CrosstermBackend::new(stdout())).unwrap(),
+            on_quit: Box::new(|| {}),
+            quit: false,
+            stopped: false,
+        }
+    }
+
+    pub fn on_quit(&mut self, callback: impl FnMut() + 'static) {
+        self.on_quit = Box::new(callback);
+    }
+
+    /// Gives the terminal back, leaving raw mode and the alternate screen. Only the first
+    /// call does anything.
+    pub fn stop(&mut self) -> io::Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+        self.stopped = true;
+        disable_raw_mode()?;
+        stdout().execute(LeaveAlternateScreen)?;
+        Ok(())
+    }
+
+    /// Whether the user closed the display with `q`.
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+
+    /// Shows a side panel for tuning these parameters with the arrow keys. Edits are
+    /// written into the driver's `Data` on the next call to `sync_params`.
+    pub fn show_params(&mut self, specs: &'static [ParamSpec]) {
+        self.params_panel = Some(ParamsPanel::new(specs));
+    }
+
+    pub fn hide_params(&mut self) {
+        self.params_panel = None;
+    }
+
+    /// Applies any parameter edits made since the last call and picks up the
+    /// current values to display.
+    pub fn sync_params(&mut self, data: &mut Data) -> SledResult {
+        match &mut self.params_panel {
+            Some(panel) => panel.sync(data),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns true once after the user pressed `s` in the parameter panel.
+    pub fn take_save_request(&mut self) -> bool {
+        self.params_panel
+            .as_mut()
+            .is_some_and(|panel| panel.take_save_request())
+    }
+
+    /// Returns the index of the last number key pressed (`1` is index 0), if any
+    /// was pressed since the previous call.
+    pub fn take_effect_request(&mut self) -> Option<usize> {
+        self.effect_request.take()
+    }
+
+    pub fn set_leds(&mut self, leds: impl Iterator<Item = (Rgb, Vec2)>) {
+        // not ideal, look for a workaround later
+        self.leds = leds.collect()
+    }
+
+    pub fn refresh(&mut self) -> io::Result<()> {
+        let should_quit = self.handle_events()?;
+        if should_quit {
+            self.quit = true;
+            (self.on_quit)();
+            self.stop()?;
+            Err(Error::other("User closed the terminal."))
+        } else {
+            self.draw_frame()?;
+            Ok(())
+        }
+    }
+
+    fn handle_events(&mut self) -> io::Result<bool> {
+        while event::poll(Duration::ZERO)? {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+
+            if key.kind != event::KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') => return Ok(true),
+                KeyCode::Char(c @ '1'..='9') => {
+                    self.effect_request = Some(c as usize - '1' as usize);
+                    continue;
+                }
+                _ => {}
+            }
+
+            if let Some(panel) = &mut self.params_panel {
+                panel.handle_key(key);
+            }
+        }
+        Ok(false)
+    }
+
+    fn draw_frame(&mut self) -> io::Result<()> {
+        let canvas = build_viewport(&self.title, self.x_bounds, self.y_bounds).paint(|ctx| {
+            for led in &self.leds {
+                draw_led(ctx, led);
+            }
+        });
+
+        let panel = &self.params_panel;
+        self.terminal.draw(|frame| match panel {
+            Some(panel) => {
+                let [canvas_area, panel_area] = Layout::horizontal([
+                    Constraint::Min(0),
+                    Constraint::Length(params_panel::WIDTH),
+                ])
+                .areas(frame.area());
+                frame.render_widget(canvas, canvas_area);
+                panel.render(frame, panel_area);
+            }
+            None => frame.render_widget(canvas, frame.area()),
+        })?;
+
+        Ok(())
+    }
+}
+
+impl LedSink for SledTerminalDisplay {
+    /// Draws the frame and handles input, failing once the user has quit.
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        self.set_leds(frame.colors_and_positions());
+        self.refresh().map_err(SledError::from_error)
+    }
+}
+
+impl Drop for SledTerminalDisplay {
+    fn drop(&mut self) {
+        // nothing left to report an error to
+        let _ = self.stop();
+    }
+}
+
+fn draw_led(ctx: &mut Context, led: &(Rgb, Vec2)) {
+    let (col, pos) = led;
+    ctx.draw(&Circle {
+        x: pos.x as f64,
+        y: pos.y as f64,
+        radius: 0.0,
+        color: Color::Rgb(
+            (col.red * 255.0) as u8,
+            (col.green * 255.0) as u8,
+            (col.blue * 255.0) as u8,
+        ),
+    });
+}
+
+fn build_viewport<T>(title: &str, x_bounds: [f64; 2], y_bounds: [f64; 2]) -> Canvas<'_, T>
+where
+    T: Fn(&mut Context<'_>),
+{
+    Canvas::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .marker(symbols::Marker::HalfBlock)
+        .background_color(Color::Black)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+}
@@ -0,0 +1,129 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+use spatial_led::{driver::Data, SledResult};
+
+use crate::params::{ParamSpec, ParamValue};
+
+pub const WIDTH: u16 = 34;
+
+enum Edit {
+    Nudge { row: usize, steps: i32 },
+    Reset { row: usize },
+}
+
+/// Lists an effect's parameters next to the canvas and turns key presses into edits
+/// of the driver's `Data`.
+pub struct ParamsPanel {
+    specs: &'static [ParamSpec],
+    // one row per float/count, two for vec2s: (spec index, component)
+    rows: Vec<(usize, usize)>,
+    values: Vec<ParamValue>,
+    selected: usize,
+    pending: Vec<Edit>,
+    save_requested: bool,
+}
+
+impl ParamsPanel {
+    pub fn new(specs: &'static [ParamSpec]) -> Self {
+        let mut rows = vec![];
+        for (i, spec) in specs.iter().enumerate() {
+            match spec.default {
+                ParamValue::Vec2(_) => rows.extend([(i, 0), (i, 1)]),
+                _ => rows.push((i, 0)),
+            }
+        }
+
+        ParamsPanel {
+            specs,
+            rows,
+            values: specs.iter().map(|spec| spec.default).collect(),
+            selected: 0,
+            pending: vec![],
+            save_requested: false,
+        }
+    }
+
+    /// Returns true if the key was meant for the panel.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.rows.is_empty() {
+            return false;
+        }
+
+        let steps = match key.modifiers.contains(KeyModifiers::SHIFT) {
+            true => 10,
+            false => 1,
+        };
+
+        match key.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(self.rows.len() - 1),
+            KeyCode::Left => self.pending.push(Edit::Nudge {
+                row: self.selected,
+                steps: -steps,
+            }),
+            KeyCode::Right => self.pending.push(Edit::Nudge {
+                row: self.selected,
+                steps,
+            }),
+            KeyCode::Char('r') => self.pending.push(Edit::Reset { row: self.selected }),
+            KeyCode::Char('s') => self.save_requested = true,
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn take_save_request(&mut self) -> bool {
+        std::mem::take(&mut self.save_requested)
+    }
+
+    /// Applies queued edits to `data`, then refreshes the values shown in the panel.
+    pub fn sync(&mut self, data: &mut Data) -> SledResult {
+        for edit in self.pending.drain(..) {
+            let (Edit::Nudge { row, .. } | Edit::Reset { row }) = edit;
+            let (index, component) = self.rows[row];
+            let spec = &self.specs[index];
+
+            let value = match edit {
+                Edit::Nudge { steps, .. } => spec.nudge(spec.load(data)?, component, steps),
+                Edit::Reset { .. } => spec.default,
+            };
+            value.store(data, spec.name);
+        }
+
+        for (spec, value) in self.specs.iter().zip(self.values.iter_mut()) {
+            *value = spec.load(data)?;
+        }
+        Ok(())
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let mut lines: Vec<Line> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(row, &(index, component))| {
+                let spec = &self.specs[index];
+                let (name, value) = match self.values[index] {
+                    ParamValue::Vec2(v) => (
+                        format!("{}.{}", spec.name, ["x", "y"][component]),
+                        format!("{:.3}", v[component]),
+                    ),
+                    value => (spec.name.to_string(), value.to_string()),
+                };
+
+                let line = Line::from(format!(" {:<19}{:>10}", name, value));
+                match row == self.selected {
+                    true => line.reversed(),
+                    false => line,
+                }
+            })
+            .collect();
+
+        lines.push(Line::default());
+        lines.push(Line::from(" ↑↓ select  ←→ adjust").dark_gray());
+        lines.push(Line::from(" shift ×10  r reset  s save").dark_gray());
+
+        let panel = Paragraph::new(lines).block(Block::bordered().title("Parameters"));
+        frame.render_widget(panel, area);
+    }
+}
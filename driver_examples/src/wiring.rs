@@ -0,0 +1,293 @@
+use std::ops::Range;
+
+use palette::rgb::Rgb;
+use serde::Deserialize;
+use spatial_led::{Sled, SledError};
+use toml::{Table, Value};
+
+use crate::{
+    params::FromTable,
+    strip::{ColorOrder, StripConfig, StripType},
+};
+
+const DEFAULT_PIN: u8 = 18;
+/// The pins each of the Pi's two PWM channels can drive, in channel order.
+const PWM_PINS: [[u8; 2]; 2] = [[12, 18], [13, 19]];
+
+/// One data line and the LEDs it drives, in the order they're wired.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelConfig {
+    /// The GPIO pin the data line is connected to.
+    pub pin: u8,
+    /// Segments of the room layout on this line, in the order they're chained.
+    #[serde(default)]
+    pub segments: Vec<usize>,
+    /// LED indices on this line as `[start, end]` with `end` excluded, after any segments.
+    pub leds: Option<[usize; 2]>,
+    /// Listed segments that are wired from their last LED to their first.
+    #[serde(default)]
+    pub reversed: Vec<usize>,
+    /// The LED the chain starts at, when it enters the room partway through the LEDs
+    /// above. The ones before it are wired after the rest, wrapping around.
+    pub start: Option<usize>,
+    /// Dead or sacrificial pixels as `[position, count]`, where `position` counts every
+    /// pixel along the line, dead ones included.
+    #[serde(default)]
+    pub dead: Vec<[usize; 2]>,
+    /// Overrides the `[strip]` settings for this line.
+    #[serde(rename = "type")]
+    pub strip_type: Option<StripType>,
+    pub order: Option<ColorOrder>,
+    pub white_temperature: Option<f32>,
+}
+
+impl ChannelConfig {
+    /// The strip on this line, taking anything not set here from `base`.
+    pub fn strip(&self, base: &StripConfig) -> StripConfig {
+        StripConfig {
+            strip_type: self.strip_type.unwrap_or(base.strip_type),
+            // every type shares the same default order, so [strip]'s still applies
+            order: self.order.or(base.order),
+            white_temperature: self.white_temperature.unwrap_or(base.white_temperature),
+        }
+    }
+}
+
+/// How the room's LEDs are split across data lines.
+///
+/// Loaded from the `[wiring]` table of a params file. Without any channels, every LED
+/// goes out on pin 18 in index order. The first channel is on pin 12 or 18 and the
+/// second on 13 or 19, and no LED can be on more than one.
+///
+/// ```toml
+/// [[wiring.channels]]
+/// pin = 18
+/// segments = [0, 1, 2]
+/// reversed = [1]        # wired back to front
+/// start = 120           # the chain enters the room at led 120
+/// dead = [[0, 1]]       # a sacrificial first pixel
+///
+/// [[wiring.channels]]
+/// pin = 13
+/// leds = [900, 1500]
+/// type = "sk6812w"      # type, order and white_temperature override [strip]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WiringConfig {
+    pub channels: Vec<ChannelConfig>,
+}
+
+impl FromTable for WiringConfig {
+    fn from_table(table: &Table) -> Result<Self, SledError> {
+        let config: WiringConfig = Value::Table(table.clone())
+            .try_into()
+            .map_err(|e| SledError::new(format!("Invalid [wiring] table: {}", e)))?;
+
+        for (i, channel) in config.channels.iter().enumerate() {
+            let Some(pins) = PWM_PINS.get(i) else {
+                return SledError::new(format!(
+                    "[wiring] has {} channels, but there are only {} PWM channels.",
+                    config.channels.len(),
+                    PWM_PINS.len()
+                ))
+                .as_err();
+            };
+            if !pins.contains(&channel.pin) {
+                return SledError::new(format!(
+                    "[wiring] channel {} is on PWM channel {}, which drives pin {} or {}, not {}.",
+                    i, i, pins[0], pins[1], channel.pin
+                ))
+                .as_err();
+            }
+
+            if let Some(kelvin) = channel.white_temperature {
+                if !(1000.0..=40_000.0).contains(&kelvin) {
+                    return SledError::new(format!(
+                        "[wiring] white_temperature should be between 1000 and 40000 kelvin, found {}.",
+                        kelvin
+                    ))
+                    .as_err();
+                }
+            }
+        }
+
+        // segments and ranges mapped twice; overlaps between the two need the room, so
+        // `Wiring::new` catches those
+        let mut segments: Vec<(usize, usize)> = vec![];
+        let mut ranges: Vec<(usize, [usize; 2])> = vec![];
+        for (i, channel) in config.channels.iter().enumerate() {
+            for &segment in &channel.segments {
+                if let Some((j, _)) = segments.iter().find(|(_, s)| *s == segment) {
+                    return SledError::new(format!(
+                        "[wiring] segment {} is on channel {} and channel {}.",
+                        segment, j, i
+                    ))
+                    .as_err();
+                }
+                segments.push((i, segment));
+            }
+            if let Some([start, end]) = channel.leds {
+                let overlap = ranges
+                    .iter()
+                    .find(|(_, [s, e])| start < *e && *s < end && start < end);
+                if let Some((j, _)) = overlap {
+                    return SledError::new(format!(
+                        "[wiring] leds {}..{} on channel {} overlap those on channel {}.",
+                        start, end, i, j
+                    ))
+                    .as_err();
+                }
+                ranges.push((i, [start, end]));
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// A data line resolved against the room: its pin, strip, and the index of the LED
+/// behind each pixel along it, if any.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+    pub pin: u8,
+    pub strip: StripConfig,
+    pub pixels: Vec<Option<usize>>,
+}
+
+static BLACK: Rgb = Rgb::new(0.0, 0.0, 0.0);
+
+impl Channel {
+    /// This line's colors in wiring order, with dead pixels kept dark.
+    pub fn colors<'a>(&'a self, colors: &'a [Rgb]) -> impl Iterator<Item = &'a Rgb> + 'a {
+        self.pixels
+            .iter()
+            .map(|pixel| pixel.and_then(|i| colors.get(i)).unwrap_or(&BLACK))
+    }
+}
+
+/// Maps a frame's colors onto the data lines they're sent over.
+#[derive(Clone, Debug)]
+pub struct Wiring {
+    channels: Vec<Channel>,
+}
+
+impl Wiring {
+    pub fn new(
+        config: &WiringConfig,
+        strip: &StripConfig,
+        sled: &Sled<Rgb>,
+    ) -> Result<Self, SledError> {
+        if config.channels.is_empty() {
+            return Ok(Wiring {
+                channels: vec![Channel {
+                    pin: DEFAULT_PIN,
+                    strip: strip.clone(),
+                    pixels: (0..sled.num_leds()).map(Some).collect(),
+                }],
+            });
+        }
+
+        let mut channels = vec![];
+        // the channel each LED is on so far
+        let mut owners: Vec<Option<usize>> = vec![None; sled.num_leds()];
+        for (i, channel) in config.channels.iter().enumerate() {
+            let mut leds = vec![];
+            for &segment in &channel.segments {
+                if segment >= sled.num_segments() {
+                    return SledError::new(format!(
+                        "Channel {} uses segment {}, but the room only has {}.",
+                        i,
+                        segment,
+                        sled.num_segments()
+                    ))
+                    .as_err();
+                }
+                let start = leds.len();
+                leds.extend(
+                    sled.leds()
+                        .filter(|led| led.segment() as usize == segment)
+                        .map(|led| led.index() as usize),
+                );
+                if channel.reversed.contains(&segment) {
+                    leds[start..].reverse();
+                }
+            }
+            if let Some(&segment) = channel
+                .reversed
+                .iter()
+                .find(|s| !channel.segments.contains(s))
+            {
+                return SledError::new(format!(
+                    "Channel {} reverses segment {}, which it doesn't use.",
+                    i, segment
+                ))
+                .as_err();
+            }
+
+            let range: Range<usize> = match channel.leds {
+                Some([start, end]) if start <= end && end <= sled.num_leds() => start..end,
+                Some([start, end]) => {
+                    return SledError::new(format!(
+                        "Channel {} uses leds {}..{}, but the room has {}.",
+                        i,
+                        start,
+                        end,
+                        sled.num_leds()
+                    ))
+                    .as_err()
+                }
+                None => 0..0,
+            };
+            leds.extend(range);
+
+            for &led in &leds {
+                if let Some(j) = owners[led].replace(i) {
+                    return SledError::new(format!(
+                        "Led {} is on channel {} and channel {}.",
+                        led, j, i
+                    ))
+                    .as_err();
+                }
+            }
+
+            if let Some(start) = channel.start {
+                let Some(offset) = leds.iter().position(|&led| led == start) else {
+                    return SledError::new(format!(
+                        "Channel {} starts at led {}, which it doesn't use.",
+                        i, start
+                    ))
+                    .as_err();
+                };
+                leds.rotate_left(offset);
+            }
+
+            let mut pixels: Vec<Option<usize>> = leds.into_iter().map(Some).collect();
+            let mut dead = channel.dead.clone();
+            dead.sort();
+            for [position, count] in dead {
+                if position > pixels.len() {
+                    return SledError::new(format!(
+                        "Channel {} has dead pixels at {}, past its end at {}.",
+                        i,
+                        position,
+                        pixels.len()
+                    ))
+                    .as_err();
+                }
+                pixels.splice(position..position, std::iter::repeat_n(None, count));
+            }
+
+            channels.push(Channel {
+                pin: channel.pin,
+                strip: channel.strip(strip),
+                pixels,
+            });
+        }
+        Ok(Wiring { channels })
+    }
+
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+}
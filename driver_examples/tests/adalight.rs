@@ -0,0 +1,85 @@
+// pseudo-terminals stand in for the USB serial port
+#![cfg(unix)]
+
+use std::{
+    io::{ErrorKind, Read},
+    time::{Duration, Instant},
+};
+
+use driver_examples::{
+    adalight::{self, AdalightSink},
+    params::ParamFile,
+    sink::{Frame, LedSink},
+};
+use palette::rgb::Rgb;
+use serialport::{SerialPort, TTYPort};
+use spatial_led::Vec2;
+
+/// Reads from the controller's end of the pty until a whole frame has arrived.
+fn read_frame(port: &mut TTYPort, buffer: &mut Vec<u8>) -> Vec<u8> {
+    let started = Instant::now();
+    let mut chunk = [0; 1024];
+    loop {
+        if let Some((data, length)) = adalight::parse(buffer) {
+            let data = data.to_vec();
+            buffer.drain(..length);
+            return data;
+        }
+        assert!(started.elapsed() < Duration::from_secs(2), "frame lost");
+        match port.read(&mut chunk) {
+            Ok(length) => buffer.extend_from_slice(&chunk[..length]),
+            Err(e) if e.kind() == ErrorKind::TimedOut => {}
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+#[test]
+fn headers_carry_the_count_and_checksum() {
+    assert_eq!(adalight::header(1), *b"Ada\x00\x00\x55");
+    // 300 LEDs are sent as 299, 0x012b
+    assert_eq!(adalight::header(300), [b'A', b'd', b'a', 0x01, 0x2b, 0x7f]);
+
+    let frame = adalight::frame(&[1, 2, 3, 4, 5, 6]);
+    assert_eq!(frame.len(), 12);
+    assert_eq!(adalight::parse(&frame[..11]), None);
+
+    // leading garbage, and a magic word with a bad checksum, are skipped
+    let mut stream = b"xxAda\x00\x00\x00".to_vec();
+    stream.extend_from_slice(&frame);
+    let (data, length) = adalight::parse(&stream).unwrap();
+    assert_eq!(data, [1, 2, 3, 4, 5, 6]);
+    assert_eq!(length, stream.len());
+}
+
+#[test]
+fn sends_frames_down_a_serial_port() {
+    let (mut controller, host) = TTYPort::pair().unwrap();
+    let path = host.name().unwrap();
+    let params = ParamFile::parse("[output]\ndither = false\n").unwrap();
+    let mut sink = AdalightSink::open(&path, 230_400, &params.output()).unwrap();
+    assert_eq!(sink.baud_rate().unwrap(), 230_400);
+
+    let colors: Vec<Rgb> = (0..300)
+        .map(|i| Rgb::new(1.0, (i % 256) as f32 / 255.0, 0.0))
+        .collect();
+    let positions = vec![Vec2::ZERO; 300];
+    let mut buffer = vec![];
+    for _ in 0..2 {
+        sink.send(&Frame::new(&colors, &positions)).unwrap();
+        let data = read_frame(&mut controller, &mut buffer);
+        assert_eq!(data.len(), 300 * 3);
+        for (i, pixel) in data.chunks_exact(3).enumerate() {
+            assert_eq!(pixel, [255, (i % 256) as u8, 0]);
+        }
+    }
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn missing_ports_are_reported() {
+    let error = AdalightSink::open("/dev/no-such-port", 115_200, &Default::default())
+        .err()
+        .unwrap();
+    assert!(error.message.contains("/dev/no-such-port"), "{}", error);
+}
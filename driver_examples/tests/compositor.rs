@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use driver_examples::{
+    clock::Driver,
+    compositor::{Compositor, LayerBlend},
+};
+use palette::rgb::Rgb;
+use spatial_led::Sled;
+
+const BASE: Rgb = Rgb::new(0.2, 0.5, 0.8);
+const LAYER: Rgb = Rgb::new(0.6, 0.5, 0.1);
+
+fn assert_close(actual: Rgb, expected: Rgb) {
+    let close = [
+        (actual.red, expected.red),
+        (actual.green, expected.green),
+        (actual.blue, expected.blue),
+    ]
+    .iter()
+    .all(|(a, e)| (a - e).abs() < 1e-4);
+    assert!(close, "{:?} isn't {:?}", actual, expected);
+}
+
+/// A driver that lights every LED `color`.
+fn solid(color: Rgb) -> Driver<Rgb> {
+    let mut driver = Driver::new();
+    driver.set_draw_commands(move |sled, _, _| {
+        sled.set_all(color);
+        Ok(())
+    });
+    driver
+}
+
+#[test]
+fn each_blend_combines_channels() {
+    let cases = [
+        (LayerBlend::Add, Rgb::new(0.8, 1.0, 0.9)),
+        (LayerBlend::Screen, Rgb::new(0.68, 0.75, 0.82)),
+        (LayerBlend::Multiply, Rgb::new(0.12, 0.25, 0.08)),
+        (LayerBlend::Max, Rgb::new(0.6, 0.5, 0.8)),
+        (LayerBlend::Alpha, LAYER),
+    ];
+    for (blend, expected) in cases {
+        assert_close(blend.apply(BASE, LAYER, 1.0), expected);
+    }
+}
+
+#[test]
+fn opacity_fades_between_base_and_blend() {
+    for blend in [
+        LayerBlend::Add,
+        LayerBlend::Screen,
+        LayerBlend::Multiply,
+        LayerBlend::Max,
+        LayerBlend::Alpha,
+    ] {
+        assert_close(blend.apply(BASE, LAYER, 0.0), BASE);
+    }
+    assert_close(
+        LayerBlend::Alpha.apply(BASE, LAYER, 0.25),
+        Rgb::new(0.3, 0.5, 0.625),
+    );
+    assert_close(
+        LayerBlend::Add.apply(BASE, LAYER, 0.5),
+        Rgb::new(0.5, 0.75, 0.85),
+    );
+    assert_close(
+        LayerBlend::Multiply.apply(BASE, LAYER, 0.5),
+        Rgb::new(0.16, 0.375, 0.44),
+    );
+}
+
+#[test]
+fn stacks_layers_bottom_first() {
+    let room = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("complex_room.yap");
+    let sled = Sled::<Rgb>::new(room.to_str().unwrap()).unwrap();
+
+    let mut compositor = Compositor::new()
+        .with_layer(solid(BASE), LayerBlend::Alpha, 1.0)
+        .with_layer(solid(LAYER), LayerBlend::Multiply, 0.5);
+    // opacity is kept within 0..=1
+    compositor.push(solid(LAYER), LayerBlend::Add, 3.0);
+    assert_eq!(compositor.layers()[2].opacity, 1.0);
+
+    let mut driver = compositor.build_driver();
+    driver.mount(sled);
+    driver.step();
+    // the base multiplied by the layer at half opacity, then the layer added on top
+    let expected = Rgb::new(0.16 + 0.6, 0.375 + 0.5, 0.44 + 0.1);
+    driver.colors().for_each(|&c| assert_close(c, expected));
+}
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use driver_examples::decay;
+
+fn remaining_after_one_second(fps: u32, half_life: f32) -> f32 {
+    let delta = Duration::from_secs(1) / fps;
+    (0..fps).map(|_| decay::factor(delta, half_life)).product()
+}
+
+#[test]
+fn trails_match_at_any_frame_rate() {
+    for half_life in [0.028, 0.35, 2.0] {
+        let expected = 0.5f32.powf(1.0 / half_life);
+        for fps in [30, 60, 144, 500] {
+            let remaining = remaining_after_one_second(fps, half_life);
+            assert!(
+                (remaining - expected).abs() <= 1e-4 + expected * 1e-3,
+                "{} fps with a {}s half-life left {}, expected {}",
+                fps,
+                half_life,
+                remaining,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn halves_every_half_life() {
+    assert!((decay::factor(Duration::from_millis(350), 0.35) - 0.5).abs() < 1e-6);
+    assert_eq!(decay::factor(Duration::ZERO, 0.35), 1.0);
+    assert_eq!(decay::factor(Duration::from_millis(16), 0.0), 0.0);
+}
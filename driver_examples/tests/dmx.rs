@@ -0,0 +1,167 @@
+use std::{net::UdpSocket, time::Duration};
+
+use driver_examples::{
+    net::{
+        artnet,
+        dmx::{DmxConfig, DmxProtocol, DmxSink},
+        sacn,
+    },
+    params::ParamFile,
+    sink::{Frame, LedSink},
+};
+use palette::rgb::Rgb;
+use spatial_led::Vec2;
+
+// linear output without dithering, so bytes can be checked exactly
+const EXACT: &str = "[output]\ndither = false\n";
+
+fn listener() -> (UdpSocket, String) {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    let address = socket.local_addr().unwrap().to_string();
+    (socket, address)
+}
+
+fn receive(socket: &UdpSocket) -> Vec<u8> {
+    let mut buffer = [0; 1500];
+    let length = socket.recv(&mut buffer).unwrap();
+    buffer[..length].to_vec()
+}
+
+fn sink(protocol: DmxProtocol, target: &str, params: &str) -> DmxSink {
+    let params = ParamFile::parse(params).unwrap();
+    DmxSink::new(protocol, Some(target), params.dmx(), &params.output()).unwrap()
+}
+
+/// Every LED gets its index as its red channel.
+fn send_ramp(sink: &mut DmxSink, leds: usize) {
+    let colors: Vec<Rgb> = (0..leds)
+        .map(|i| Rgb::new((i % 256) as f32 / 255.0, 0.0, 1.0))
+        .collect();
+    let positions = vec![Vec2::ZERO; leds];
+    sink.send(&Frame::new(&colors, &positions)).unwrap();
+}
+
+#[test]
+fn art_net_wraps_universes_at_170_pixels() {
+    let (socket, address) = listener();
+    let mut sink = sink(DmxProtocol::ArtNet, &address, EXACT);
+    send_ramp(&mut sink, 200);
+
+    let first = receive(&socket);
+    assert_eq!(first[..8], *b"Art-Net\0");
+    assert_eq!(first[8..10], [0x00, 0x50]); // OpDmx, little endian
+    assert_eq!(first[10..12], [0, 14]);
+    assert_eq!(first[12], 1); // sequence
+    assert_eq!(first[14..16], [1, 0]); // universe 1
+    assert_eq!(first[16..18], 510u16.to_be_bytes());
+    assert_eq!(first[18..24], [0, 0, 255, 1, 0, 255]);
+    assert_eq!(first[18 + 169 * 3], 169);
+
+    let second = receive(&socket);
+    assert_eq!(second[14..16], [2, 0]);
+    assert_eq!(second[16..18], 90u16.to_be_bytes());
+    assert_eq!(second[18], 170);
+    assert_eq!(second.len(), 18 + 90);
+
+    assert_eq!(receive(&socket), artnet::sync_packet());
+
+    // each universe counts its own frames
+    send_ramp(&mut sink, 200);
+    assert_eq!(receive(&socket)[12], 2);
+    assert_eq!(receive(&socket)[12], 2);
+}
+
+#[test]
+fn sacn_sends_prioritized_universes_then_a_sync() {
+    let (socket, address) = listener();
+    let params = format!(
+        "{}[dmx]\nstart_universe = 7\npixels_per_universe = 100\npriority = 150\n",
+        EXACT
+    );
+    let mut sink = sink(DmxProtocol::Sacn, &address, &params);
+    send_ramp(&mut sink, 150);
+
+    let first = receive(&socket);
+    assert_eq!(first[4..16], *b"ASC-E1.17\0\0\0");
+    assert_eq!(first.len(), 126 + 300);
+    assert_eq!(first[108], 150); // priority
+    assert_eq!(first[109..111], 7u16.to_be_bytes()); // synchronization universe
+    assert_eq!(first[111], 1); // sequence
+    assert_eq!(first[113..115], 7u16.to_be_bytes());
+    assert_eq!(first[125], 0); // start code
+    assert_eq!(first[126..129], [0, 0, 255]);
+
+    let second = receive(&socket);
+    assert_eq!(second.len(), 126 + 150);
+    assert_eq!(second[113..115], 8u16.to_be_bytes());
+    assert_eq!(second[126], 100);
+    // both universes come from the same source
+    assert_eq!(first[22..38], second[22..38]);
+
+    let sync = receive(&socket);
+    assert_eq!(sync.len(), 49);
+    assert_eq!(sync[22..38], first[22..38]);
+    assert_eq!(sync[45..47], 7u16.to_be_bytes());
+}
+
+#[test]
+fn four_channels_per_pixel_sends_white() {
+    let (socket, address) = listener();
+    let params = format!(
+        "{}[dmx]\nchannels_per_pixel = 4\nwhite_temperature = 6600\nsync = false\n",
+        EXACT
+    );
+    let mut sink = sink(DmxProtocol::ArtNet, &address, &params);
+    assert_eq!(sink.config().pixels_per_universe(), 128);
+
+    let colors = vec![Rgb::new(1.0, 1.0, 1.0); 130];
+    let positions = vec![Vec2::ZERO; 130];
+    sink.send(&Frame::new(&colors, &positions)).unwrap();
+
+    let first = receive(&socket);
+    assert_eq!(first[16..18], 512u16.to_be_bytes());
+    assert_eq!(first[18..22], [0, 0, 0, 255]);
+    let second = receive(&socket);
+    assert_eq!(second[16..18], 8u16.to_be_bytes());
+
+    // no sync packet follows
+    socket
+        .set_read_timeout(Some(Duration::from_millis(50)))
+        .unwrap();
+    assert!(socket.recv(&mut [0; 1500]).is_err());
+}
+
+#[test]
+fn rejects_bad_layouts() {
+    assert!(ParamFile::parse("[dmx]\nchannels_per_pixel = 5\n").is_err());
+    assert!(ParamFile::parse("[dmx]\npixels_per_universe = 171\n").is_err());
+    assert!(ParamFile::parse("[dmx]\npriority = 201\n").is_err());
+    assert!(ParamFile::parse("[dmx]\nuniverse = 1\n").is_err());
+
+    // sACN can't reach past universe 63999
+    let config = DmxConfig {
+        start_universe: sacn::MAX_UNIVERSE,
+        ..DmxConfig::default()
+    };
+    let mut sink = DmxSink::new(
+        DmxProtocol::Sacn,
+        Some("127.0.0.1:9"),
+        config,
+        &Default::default(),
+    )
+    .unwrap();
+    let colors = vec![Rgb::new(0.0, 0.0, 0.0); 171];
+    let positions = vec![Vec2::ZERO; 171];
+    assert!(sink.send(&Frame::new(&colors, &positions)).is_err());
+}
+
+#[test]
+fn parses_protocol_names() {
+    assert_eq!("artnet".parse(), Ok(DmxProtocol::ArtNet));
+    assert_eq!("E1.31".parse(), Ok(DmxProtocol::Sacn));
+    assert!("dmx".parse::<DmxProtocol>().is_err());
+    assert_eq!(DmxProtocol::Sacn.to_string(), "sacn");
+}
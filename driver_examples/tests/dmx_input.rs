@@ -0,0 +1,214 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+    thread,
+    time::{Duration, Instant},
+};
+
+use driver_examples::{
+    clock,
+    net::{
+        dmx::{DmxConfig, DmxProtocol, DmxSink},
+        input::{DmxInput, InputLayer, NetworkInput},
+        merge::{MergeMode, Merger, SourceId},
+        sacn,
+    },
+    params::ParamFile,
+    sink::{Frame, LedSink},
+};
+use palette::rgb::Rgb;
+use spatial_led::Vec2;
+
+const TIMEOUT: Duration = Duration::from_millis(2500);
+const BLACK: Rgb = Rgb::new(0.0, 0.0, 0.0);
+
+fn source(n: u8) -> SourceId {
+    SourceId::Address(IpAddr::V4(Ipv4Addr::new(10, 0, 0, n)))
+}
+
+/// Polls until `count` packets have been handled, since they can take a moment to
+/// arrive even over loopback.
+fn poll_for(input: &mut DmxInput, count: usize) {
+    let started = Instant::now();
+    let mut received = 0;
+    while received < count {
+        assert!(started.elapsed() < Duration::from_secs(2), "packets lost");
+        received += input.poll().unwrap();
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn input(protocol: DmxProtocol, params: &str) -> (DmxInput, SocketAddr) {
+    let params = ParamFile::parse(params).unwrap();
+    let mut input = DmxInput::new(params.dmx(), &params.output());
+    let address = input.bind(protocol, "127.0.0.1:0").unwrap();
+    (input, address)
+}
+
+#[test]
+fn highest_priority_sources_win() {
+    let mut merger = Merger::new(MergeMode::Htp, TIMEOUT);
+    assert_eq!(merger.universe(1), None);
+
+    merger.receive(source(1), 1, 100, None, None, &[10, 200, 0]);
+    merger.receive(source(2), 1, 100, None, None, &[50, 20]);
+    assert_eq!(merger.universe(1), Some(&[50, 200, 0][..]));
+
+    merger.receive(source(3), 1, 150, None, None, &[1, 2, 3]);
+    assert_eq!(merger.universe(1), Some(&[1, 2, 3][..]));
+    assert_eq!(merger.source_count(1), 3);
+
+    // the rest take over once it leaves
+    merger.terminate(source(3), 1);
+    assert_eq!(merger.universe(1), Some(&[50, 200, 0][..]));
+    assert_eq!(merger.universe(2), None);
+}
+
+#[test]
+fn latest_takes_precedence() {
+    let mut merger = Merger::new(MergeMode::Ltp, TIMEOUT);
+    merger.receive(source(1), 1, 100, None, None, &[10, 200]);
+    merger.receive(source(2), 1, 100, None, None, &[50, 20]);
+    assert_eq!(merger.universe(1), Some(&[50, 20][..]));
+    merger.receive(source(1), 1, 100, None, None, &[11, 201]);
+    assert_eq!(merger.universe(1), Some(&[11, 201][..]));
+}
+
+#[test]
+fn drops_stale_packets_and_quiet_sources() {
+    // sources time out by the wall clock, even on a thread whose effect clock is frozen
+    clock::freeze();
+    let mut merger = Merger::new(MergeMode::Htp, Duration::from_millis(300));
+    assert!(merger.receive(source(1), 1, 100, Some(10), None, &[10]));
+    assert!(!merger.receive(source(1), 1, 100, Some(9), None, &[9]));
+    assert!(!merger.receive(source(1), 1, 100, Some(10), None, &[9]));
+    // far enough behind that the sequence must have wrapped
+    assert!(merger.receive(source(1), 1, 100, Some(200), None, &[200]));
+    assert_eq!(merger.universe(1), Some(&[200][..]));
+
+    thread::sleep(Duration::from_millis(200));
+    merger.receive(source(2), 1, 100, None, None, &[1]);
+    thread::sleep(Duration::from_millis(150));
+    merger.expire();
+    assert_eq!(merger.source_count(1), 1);
+    assert_eq!(merger.universe(1), Some(&[1][..]));
+    clock::unfreeze();
+}
+
+#[test]
+fn holds_synced_data_until_released() {
+    let mut merger = Merger::new(MergeMode::Htp, TIMEOUT);
+    merger.receive(source(1), 1, 100, None, Some(7), &[10]);
+    merger.receive(source(1), 2, 100, None, Some(8), &[20]);
+    assert_eq!(merger.universe(1), None);
+
+    merger.release(source(2), Some(7));
+    merger.release(source(1), Some(7));
+    assert_eq!(merger.universe(1), Some(&[10][..]));
+    assert_eq!(merger.universe(2), None);
+    merger.release(source(1), None);
+    assert_eq!(merger.universe(2), Some(&[20][..]));
+}
+
+#[test]
+fn art_net_round_trips_through_the_sink() {
+    let (mut input, address) = input(
+        DmxProtocol::ArtNet,
+        "[output]\ndither = false\ngamma = 2.2\n[dmx]\nlayer = \"replace\"\n",
+    );
+    let params = ParamFile::parse("[output]\ndither = false\ngamma = 2.2\n").unwrap();
+    let mut sink = DmxSink::new(
+        DmxProtocol::ArtNet,
+        Some(&address.to_string()),
+        params.dmx(),
+        &params.output(),
+    )
+    .unwrap();
+
+    let colors: Vec<Rgb> = (0..200)
+        .map(|i| Rgb::new(0.25 + i as f32 / 400.0, 1.0, 0.25))
+        .collect();
+    let positions = vec![Vec2::ZERO; 200];
+    for _ in 0..2 {
+        sink.send(&Frame::new(&colors, &positions)).unwrap();
+        // two universes and a sync
+        poll_for(&mut input, 3);
+    }
+
+    // gamma is undone, so colors come back to within a step of the 8-bit encoding
+    let mut received = vec![BLACK; 210];
+    input.apply(&mut received);
+    for (sent, received) in colors.iter().zip(&received) {
+        assert!((sent.red - received.red).abs() < 0.02, "{:?}", received);
+        assert!((sent.blue - received.blue).abs() < 0.02);
+    }
+    // LEDs past the end of what was sent are dark
+    assert_eq!(received[205], BLACK);
+}
+
+#[test]
+fn sacn_waits_for_sync_and_respects_priority() {
+    let (mut input, address) = input(DmxProtocol::Sacn, "[dmx]\nlayer = \"over\"\n");
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let low = sacn::Source {
+        cid: [1; 16],
+        name: "low".to_string(),
+        priority: 50,
+    };
+    let high = sacn::Source {
+        cid: [2; 16],
+        name: "high".to_string(),
+        priority: 120,
+    };
+
+    let send = |packet: Vec<u8>| sender.send_to(&packet, address).unwrap();
+    send(sacn::data_packet(&low, 1, 1, 0, &[255, 0, 0]));
+    send(sacn::data_packet(&high, 1, 1, 1, &[0, 255, 0]));
+    poll_for(&mut input, 2);
+
+    let driver = Rgb::new(0.0, 0.0, 1.0);
+    let mut colors = vec![driver; 200];
+    input.apply(&mut colors);
+    // the high priority source is waiting for its sync, and the second universe is empty
+    assert_eq!(colors[0], Rgb::new(1.0, 0.0, 0.0));
+    assert_eq!(colors[1], BLACK);
+    assert_eq!(colors[199], driver);
+
+    send(sacn::sync_packet(&high.cid, 1, 1));
+    poll_for(&mut input, 1);
+    input.apply(&mut colors);
+    assert_eq!(colors[0], Rgb::new(0.0, 1.0, 0.0));
+
+    // a terminated stream hands the universe straight back
+    let mut terminated = sacn::data_packet(&high, 1, 2, 0, &[0, 255, 0]);
+    terminated[112] |= 0x40;
+    send(terminated);
+    poll_for(&mut input, 1);
+    input.apply(&mut colors);
+    assert_eq!(colors[0], Rgb::new(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn htp_layer_keeps_the_brighter_channel() {
+    let config = DmxConfig {
+        layer: InputLayer::Htp,
+        channels_per_pixel: 4,
+        white_temperature: 6600.0,
+        ..DmxConfig::default()
+    };
+    let mut input = DmxInput::new(config, &Default::default());
+    input
+        .merger()
+        .receive(source(1), 1, 100, None, None, &[0, 0, 0, 255, 0, 0, 51, 0]);
+
+    let mut colors = vec![Rgb::new(0.5, 0.5, 0.5); 2];
+    input.apply(&mut colors);
+    assert_eq!(colors[0], Rgb::new(1.0, 1.0, 1.0));
+    assert_eq!(colors[1], Rgb::new(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn rejects_bad_input_settings() {
+    assert!(ParamFile::parse("[dmx]\nmerge = \"loudest\"\n").is_err());
+    assert!(ParamFile::parse("[dmx]\nlayer = \"under\"\n").is_err());
+    assert!(ParamFile::parse("[dmx]\nsource_timeout = 0\n").is_err());
+}
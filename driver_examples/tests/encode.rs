@@ -0,0 +1,70 @@
+use driver_examples::{
+    encode::{Encoder, EncoderConfig, Gamma},
+    params::ParamFile,
+};
+use palette::rgb::Rgb;
+
+fn config(gamma: Gamma, dither: bool) -> EncoderConfig {
+    EncoderConfig { gamma, dither }
+}
+
+#[test]
+fn linear_without_dithering_rounds() {
+    let mut encoder = Encoder::new(&config(Gamma::Uniform(1.0), false));
+    let colors = [Rgb::new(1.0, 0.5, 0.0), Rgb::new(2.0, -1.0, f32::NAN)];
+    assert_eq!(
+        encoder.encode(colors.iter()),
+        &[[255, 128, 0, 0], [255, 0, 0, 0]]
+    );
+}
+
+#[test]
+fn gamma_per_channel() {
+    let mut encoder = Encoder::new(&config(Gamma::PerChannel([1.0, 2.0, 3.0]), false));
+    let colors = [Rgb::new(0.5, 0.5, 0.5)];
+    assert_eq!(encoder.encode(colors.iter()), &[[128, 64, 32, 0]]);
+}
+
+#[test]
+fn dithering_averages_out_to_the_exact_value() {
+    let mut encoder = Encoder::new(&config(Gamma::Uniform(2.2), true));
+    let color = Rgb::new(0.05, 0.1, 0.2);
+    let frames = 1000;
+
+    let mut sums = [0u32; 3];
+    for _ in 0..frames {
+        let [encoded] = encoder.encode([color].iter()) else {
+            panic!("expected a single led");
+        };
+        for c in 0..3 {
+            sums[c] += encoded[c] as u32;
+        }
+    }
+
+    for (c, value) in [color.red, color.green, color.blue].into_iter().enumerate() {
+        let average = sums[c] as f32 / frames as f32;
+        let expected = encoder.correct(c, value);
+        assert!(
+            (average - expected).abs() < 0.01,
+            "channel {} averaged {}, expected {}",
+            c,
+            average,
+            expected
+        );
+    }
+}
+
+#[test]
+fn loads_from_params_file() {
+    let params = ParamFile::parse("[output]\ngamma = [2.2, 2.0, 2.4]\ndither = false\n").unwrap();
+    assert_eq!(
+        params.output(),
+        config(Gamma::PerChannel([2.2, 2.0, 2.4]), false)
+    );
+
+    let params = ParamFile::parse("[output]\ngamma = 2.5\n").unwrap();
+    assert_eq!(params.output(), config(Gamma::Uniform(2.5), true));
+
+    assert!(ParamFile::parse("[output]\ngamma = 0\n").is_err());
+    assert!(ParamFile::parse("[output]\ngamma = [1.0, 2.0]\n").is_err());
+}
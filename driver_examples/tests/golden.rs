@@ -0,0 +1,143 @@
+//! Golden-frame tests: each effect is stepped on `complex_room.yap` with a fixed seed
+//! and timestep, and a few of its frames are compared against the snapshots stored in
+//! `tests/golden`.
+//!
+//! After an intentional change to how an effect looks, regenerate the snapshots with
+//! `UPDATE_GOLDENS=1 cargo test --test golden` and review the diff.
+
+use std::{fmt::Write, fs, path::PathBuf, time::Duration};
+
+use driver_examples::{clock, registry, rng};
+use palette::rgb::Rgb;
+use spatial_led::Sled;
+
+const ROOM: &str = "complex_room.yap";
+const SEED: u64 = 42;
+const TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 30);
+const FRAMES: usize = 300;
+const SNAPSHOT_EVERY: usize = 100;
+const TOLERANCE: f32 = 1e-3;
+
+fn render(effect: &str) -> Vec<(usize, Vec<Rgb>)> {
+    let info = registry::find(effect).unwrap();
+    let room = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(ROOM);
+    let sled = Sled::<Rgb>::new(room.to_str().unwrap()).unwrap();
+
+    clock::freeze();
+    let mut driver = info.build_driver();
+    rng::set_seed(driver.data_mut(), SEED);
+    driver.mount(sled);
+
+    let mut snapshots = vec![];
+    for frame in 1..=FRAMES {
+        clock::advance(TIMESTEP);
+        driver.step();
+        if frame % SNAPSHOT_EVERY == 0 {
+            snapshots.push((frame, driver.colors().copied().collect()));
+        }
+    }
+    snapshots
+}
+
+fn golden_path(effect: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.txt", effect))
+}
+
+fn serialize(effect: &str, snapshots: &[(usize, Vec<Rgb>)]) -> String {
+    let mut out = format!(
+        "# {} on {}, seed {}, {:?} timestep\n",
+        effect, ROOM, SEED, TIMESTEP
+    );
+    for (frame, colors) in snapshots {
+        writeln!(out, "frame {}", frame).unwrap();
+        for c in colors {
+            writeln!(out, "{:.4} {:.4} {:.4}", c.red, c.green, c.blue).unwrap();
+        }
+    }
+    out
+}
+
+fn deserialize(source: &str) -> Vec<(usize, Vec<[f32; 3]>)> {
+    let mut snapshots: Vec<(usize, Vec<[f32; 3]>)> = vec![];
+    for line in source.lines().filter(|line| !line.starts_with('#')) {
+        if let Some(frame) = line.strip_prefix("frame ") {
+            snapshots.push((frame.parse().unwrap(), vec![]));
+            continue;
+        }
+
+        let channels: Vec<f32> = line.split(' ').map(|c| c.parse().unwrap()).collect();
+        let (_, colors) = snapshots.last_mut().expect("color before any frame header");
+        colors.push([channels[0], channels[1], channels[2]]);
+    }
+    snapshots
+}
+
+fn matches(actual: f32, expected: f32) -> bool {
+    match actual.is_finite() && expected.is_finite() {
+        // relative for the odd very bright led, absolute for everything else
+        true => (actual - expected).abs() <= TOLERANCE * expected.abs().max(1.0),
+        false => actual.is_nan() && expected.is_nan() || actual == expected,
+    }
+}
+
+fn check(effect: &str) {
+    let snapshots = render(effect);
+    let path = golden_path(effect);
+
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, serialize(effect, &snapshots)).unwrap();
+        return;
+    }
+
+    let source = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "missing golden frames at {} ({}), run `UPDATE_GOLDENS=1 cargo test --test golden`",
+            path.display(),
+            e
+        )
+    });
+    let golden = deserialize(&source);
+    assert_eq!(golden.len(), snapshots.len(), "{}: snapshot count", effect);
+
+    for ((frame, colors), (golden_frame, golden_colors)) in snapshots.iter().zip(&golden) {
+        assert_eq!(frame, golden_frame, "{}: snapshot frames", effect);
+        assert_eq!(colors.len(), golden_colors.len(), "{}: led count", effect);
+
+        for (i, (color, expected)) in colors.iter().zip(golden_colors).enumerate() {
+            let actual = [color.red, color.green, color.blue];
+            assert!(
+                actual.iter().zip(expected).all(|(a, e)| matches(*a, *e)),
+                "{}: led {} on frame {} is {:?}, expected {:?}",
+                effect,
+                i,
+                frame,
+                actual,
+                expected
+            );
+        }
+    }
+}
+
+macro_rules! golden_tests {
+    ($($effect:ident),*) => {
+        $(
+            #[test]
+            fn $effect() {
+                check(stringify!($effect));
+            }
+        )*
+
+        #[test]
+        fn every_effect_has_golden_frames() {
+            let tested = [$(stringify!($effect)),*];
+            for name in registry::names() {
+                assert!(tested.contains(&name), "no golden test for `{}`", name);
+            }
+        }
+    };
+}
+
+golden_tests!(comet, embers, ripples, scan, warpspeed);
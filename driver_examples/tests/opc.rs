@@ -0,0 +1,104 @@
+use std::{
+    io::Write,
+    net::TcpStream,
+    thread,
+    time::{Duration, Instant},
+};
+
+use driver_examples::{
+    net::{
+        input::{InputLayer, NetworkInput},
+        opc::{self, OpcServer, OpcSink},
+    },
+    params::ParamFile,
+    sink::{Frame, LedSink},
+};
+use palette::rgb::Rgb;
+use spatial_led::Vec2;
+
+const BLACK: Rgb = Rgb::new(0.0, 0.0, 0.0);
+
+/// Polls until `count` messages have been shown, since they can take a moment to
+/// arrive even over loopback.
+fn poll_for(server: &mut OpcServer, count: usize) {
+    let started = Instant::now();
+    let mut received = 0;
+    while received < count {
+        assert!(started.elapsed() < Duration::from_secs(2), "messages lost");
+        received += server.poll().unwrap();
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn server(leds: usize) -> OpcServer {
+    OpcServer::bind("127.0.0.1:0", leds, &Default::default()).unwrap()
+}
+
+#[test]
+fn parses_whole_messages_only() {
+    let message = opc::set_pixels(3, &[1, 2, 3, 4, 5, 6]);
+    assert_eq!(message[..4], [3, 0, 0, 6]);
+
+    assert_eq!(opc::parse(&message[..5]), None);
+    let mut stream = message.clone();
+    stream.extend_from_slice(&[0, 255, 0]);
+    let (parsed, length) = opc::parse(&stream).unwrap();
+    assert_eq!(length, 10);
+    assert_eq!(parsed.channel, 3);
+    assert_eq!(parsed.command, opc::SET_PIXELS);
+    assert_eq!(parsed.data, [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn round_trips_through_the_sink() {
+    let mut server = server(300);
+    let address = server.local_addr().unwrap().to_string();
+    let params = ParamFile::parse("[output]\ndither = false\n").unwrap();
+    let mut sink = OpcSink::connect(&address, 0, &params.output()).unwrap();
+
+    let colors: Vec<Rgb> = (0..300)
+        .map(|i| Rgb::new((i % 256) as f32 / 255.0, 0.0, 1.0))
+        .collect();
+    let positions = vec![Vec2::ZERO; 300];
+    sink.send(&Frame::new(&colors, &positions)).unwrap();
+    poll_for(&mut server, 1);
+    assert_eq!(server.client_count(), 1);
+
+    let mut received = vec![BLACK; 300];
+    server.apply(&mut received);
+    assert_eq!(received, colors);
+
+    // the LEDs are let go once the client leaves
+    drop(sink);
+    let started = Instant::now();
+    while server.client_count() > 0 {
+        assert!(started.elapsed() < Duration::from_secs(2));
+        server.poll().unwrap();
+    }
+    let driver = Rgb::new(0.0, 1.0, 0.0);
+    let mut received = vec![driver; 300];
+    server.with_layer(InputLayer::Over).apply(&mut received);
+    assert_eq!(received, vec![driver; 300]);
+}
+
+#[test]
+fn filters_channels_and_reassembles_split_messages() {
+    let mut server = server(2).with_channel(2);
+    let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+
+    let mut messages = opc::set_pixels(1, &[255, 0, 0]);
+    messages.extend(opc::set_pixels(2, &[0, 255, 0]));
+    messages.extend(opc::set_pixels(0, &[0, 0, 0, 0, 0, 255]));
+    // split mid-header, as a slow connection might
+    client.write_all(&messages[..5]).unwrap();
+    client.flush().unwrap();
+    thread::sleep(Duration::from_millis(20));
+    server.poll().unwrap();
+    client.write_all(&messages[5..]).unwrap();
+    poll_for(&mut server, 2);
+
+    let mut colors = vec![BLACK; 2];
+    server.apply(&mut colors);
+    // channel 1 was skipped, then channel 2 and the broadcast landed
+    assert_eq!(colors, [BLACK, Rgb::new(0.0, 0.0, 1.0)]);
+}
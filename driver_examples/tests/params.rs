@@ -0,0 +1,113 @@
+use driver_examples::{
+    params::{self, ParamFile, ParamSpec, ParamValue},
+    registry,
+};
+use spatial_led::Vec2;
+
+fn error(source: &str) -> String {
+    ParamFile::parse(source).unwrap_err().to_string()
+}
+
+#[test]
+fn applies_values_to_the_effect() {
+    let params = ParamFile::parse(
+        "seed = 7\n[warpspeed]\nnum_stars = 100\nvelocity = 9\ndirection = [0.0, -1.0]\n",
+    )
+    .unwrap();
+    assert_eq!(params.seed(), Some(7));
+
+    let effect = registry::find("warpspeed").unwrap();
+    let driver = effect.build_driver_with(&params).unwrap();
+    let data = driver.data();
+    assert_eq!(*data.get::<usize>("num_stars").unwrap(), 100);
+    // integers are fine where a float is expected
+    assert_eq!(*data.get::<f32>("velocity").unwrap(), 9.0);
+    assert_eq!(
+        *data.get::<Vec2>("direction").unwrap(),
+        Vec2::new(0.0, -1.0)
+    );
+}
+
+#[test]
+fn rejects_out_of_range_values() {
+    let e = error("[warpspeed]\nvelocity = 51.0\n");
+    assert!(e.contains("`velocity` must be within"), "{}", e);
+    let e = error("[warpspeed]\ndirection = [0.0, -2.0]\n");
+    assert!(e.contains("`direction`"), "{}", e);
+    assert!(ParamFile::parse("[ripples]\nmax_ripples = 257\n").is_err());
+    assert!(ParamFile::parse("[ripples]\nmax_ripples = -1\n").is_err());
+    assert!(ParamFile::parse("seed = -1\n").is_err());
+}
+
+#[test]
+fn rejects_wrongly_typed_values() {
+    let e = error("[warpspeed]\nnum_stars = 10.5\n");
+    assert!(e.contains("expects a count"), "{}", e);
+    for source in [
+        "[warpspeed]\nvelocity = \"fast\"\n",
+        "[warpspeed]\ndirection = 1.0\n",
+        "[warpspeed]\ndirection = [1.0, 0.0, 0.0]\n",
+        "[warpspeed]\ndirection = [\"x\", 0.0]\n",
+        "seed = \"42\"\n",
+        "warpspeed = 1\n",
+    ] {
+        assert!(ParamFile::parse(source).is_err(), "{}", source);
+    }
+}
+
+#[test]
+fn rejects_unknown_tables_and_parameters() {
+    let e = error("[warpsped]\nvelocity = 9.0\n");
+    assert!(e.contains("`[warpsped]`"), "{}", e);
+    let e = error("[warpspeed]\nspeed = 9.0\n");
+    assert!(e.contains("no parameter named `speed`"), "{}", e);
+}
+
+#[test]
+fn matches_effect_tables_ignoring_case() {
+    let params = ParamFile::parse("[WarpSpeed]\nvelocity = 9.0\n").unwrap();
+    let effect = registry::find("warpspeed").unwrap();
+    let driver = effect.build_driver_with(&params).unwrap();
+    assert_eq!(*driver.data().get::<f32>("velocity").unwrap(), 9.0);
+
+    let e = error("[warpspeed]\nvelocity = 9.0\n[Warpspeed]\nvelocity = 4.0\n");
+    assert!(e.contains("more than one table"), "{}", e);
+}
+
+#[test]
+fn specs_clamp_and_nudge_within_range() {
+    let spec = ParamSpec::float("velocity", 5.0, 0.0..=50.0);
+    assert_eq!(spec.clamp(ParamValue::Float(80.0)), ParamValue::Float(50.0));
+    assert_eq!(
+        spec.nudge(ParamValue::Float(5.0), 0, 2),
+        ParamValue::Float(6.0)
+    );
+    assert_eq!(
+        spec.nudge(ParamValue::Float(0.2), 0, -1),
+        ParamValue::Float(0.0)
+    );
+
+    let spec = ParamSpec::count("stars", 10, 0..=20);
+    // counts step by at least one
+    assert_eq!(
+        spec.nudge(ParamValue::Count(10), 0, 1),
+        ParamValue::Count(11)
+    );
+}
+
+#[test]
+fn records_and_round_trips_through_toml() {
+    let effect = registry::find("ripples").unwrap();
+    let params = ParamFile::parse("[ripples]\nmax_radius = 20.0\n").unwrap();
+    let mut driver = effect.build_driver_with(&params).unwrap();
+    // what mounting the driver would do
+    params::store_defaults(driver.data_mut(), effect.params);
+
+    let mut recorded = ParamFile::default();
+    recorded
+        .record("ripples", effect.params, driver.data())
+        .unwrap();
+    let reparsed = ParamFile::parse(&recorded.to_string()).unwrap();
+    let driver = effect.build_driver_with(&reparsed).unwrap();
+    assert_eq!(*driver.data().get::<f32>("max_radius").unwrap(), 20.0);
+}
@@ -0,0 +1,167 @@
+use std::{path::PathBuf, time::Duration};
+
+use driver_examples::{
+    clock,
+    params::ParamFile,
+    playlist::{Playlist, PlaylistMode},
+};
+use palette::rgb::Rgb;
+use spatial_led::Sled;
+
+const EFFECTS: &str = r#"
+[[effects]]
+effect = "comet"
+duration = 1
+
+[[effects]]
+effect = "scan"
+duration = 2
+
+[[effects]]
+effect = "ripples"
+duration = 3
+"#;
+
+fn playlist(header: &str) -> Playlist {
+    Playlist::parse(&format!("{}\n{}", header, EFFECTS)).unwrap()
+}
+
+/// The next `count` entries the playlist moves on to.
+fn names(playlist: &mut Playlist, count: usize) -> Vec<&'static str> {
+    (0..count).map(|_| playlist.advance().effect.name).collect()
+}
+
+fn room() -> Sled<Rgb> {
+    let room = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("complex_room.yap");
+    Sled::new(room.to_str().unwrap()).unwrap()
+}
+
+#[test]
+fn sequential_plays_in_order() {
+    let mut playlist = playlist("");
+    assert_eq!(playlist.mode, PlaylistMode::Sequential);
+    assert_eq!(playlist.current().effect.name, "comet");
+    assert_eq!(
+        names(&mut playlist, 5),
+        ["scan", "ripples", "comet", "scan", "ripples"]
+    );
+}
+
+#[test]
+fn shuffle_repeats_with_a_fixed_seed() {
+    let mut first = playlist("mode = \"shuffle\"\nseed = 7");
+    let mut second = playlist("mode = \"shuffle\"\nseed = 7");
+    assert_eq!(first.current().effect.name, second.current().effect.name);
+
+    let mut played = vec![first.current().effect.name];
+    played.extend(names(&mut first, 29));
+    assert_eq!(played[1..], names(&mut second, 29));
+
+    // every round plays each entry once
+    for round in played.chunks(3) {
+        let mut round = round.to_vec();
+        round.sort();
+        assert_eq!(round, ["comet", "ripples", "scan"]);
+    }
+    // and never plays one back to back across reshuffles
+    for pair in played.windows(2) {
+        assert_ne!(pair[0], pair[1]);
+    }
+}
+
+#[test]
+fn weighted_favors_heavier_entries() {
+    let mut playlist = Playlist::parse(
+        r#"
+        mode = "weighted"
+        seed = 3
+
+        [[effects]]
+        effect = "comet"
+        duration = 1
+        weight = 8.0
+
+        [[effects]]
+        effect = "scan"
+        duration = 1
+        weight = 1.0
+
+        [[effects]]
+        effect = "ripples"
+        duration = 1
+        weight = 0.0
+        "#,
+    )
+    .unwrap();
+
+    let picks = names(&mut playlist, 1000);
+    assert!(!picks.contains(&"ripples"));
+    // with ripples out and no repeats, the picks can only alternate
+    for pair in picks.windows(2) {
+        assert_ne!(pair[0], pair[1]);
+    }
+    let comets = picks.iter().filter(|&&name| name == "comet").count();
+    assert_eq!(comets, 500);
+
+    // without the no-repeat rule to force it, the weights decide
+    let mut playlist = Playlist::parse(
+        r#"
+        mode = "weighted"
+        seed = 3
+
+        [[effects]]
+        effect = "comet"
+        duration = 1
+        weight = 8.0
+
+        [[effects]]
+        effect = "scan"
+        duration = 1
+        weight = 1.0
+
+        [[effects]]
+        effect = "ripples"
+        duration = 1
+        weight = 1.0
+        "#,
+    )
+    .unwrap();
+    let picks = names(&mut playlist, 1000);
+    let count = |effect: &str| picks.iter().filter(|&&name| name == effect).count();
+    assert!(count("comet") > 400, "{}", count("comet"));
+    assert!(count("scan") > 100 && count("ripples") > 100);
+}
+
+#[test]
+fn moves_on_once_each_duration_is_up() {
+    clock::freeze();
+    let mut playlist = playlist("transition = 0.5");
+    let base = ParamFile::default();
+    let mut fader = playlist.start(room(), &base).unwrap();
+
+    clock::advance(Duration::from_millis(900));
+    fader.step();
+    assert!(playlist.update(&mut fader, &base).unwrap().is_none());
+
+    clock::advance(Duration::from_millis(200));
+    fader.step();
+    let entry = playlist.update(&mut fader, &base).unwrap();
+    assert_eq!(entry.map(|entry| entry.effect.name), Some("scan"));
+    assert!(fader.is_transitioning());
+    assert_eq!(fader.target().elapsed(), Duration::ZERO);
+
+    // scan's two seconds count from when it started fading in
+    clock::advance(Duration::from_millis(1900));
+    fader.step();
+    assert!(!fader.is_transitioning());
+    assert!(playlist.update(&mut fader, &base).unwrap().is_none());
+
+    clock::advance(Duration::from_millis(100));
+    let entry = playlist.update(&mut fader, &base).unwrap();
+    assert_eq!(entry.map(|entry| entry.effect.name), Some("ripples"));
+
+    // and after the last entry, back to the first
+    clock::advance(Duration::from_secs(3));
+    let entry = playlist.update(&mut fader, &base).unwrap();
+    assert_eq!(entry.map(|entry| entry.effect.name), Some("comet"));
+}
@@ -0,0 +1,79 @@
+use driver_examples::{
+    params::ParamFile,
+    post::{PostProcess, Tonemap},
+};
+use palette::rgb::Rgb;
+
+fn close(a: Rgb, b: Rgb) -> bool {
+    (a.red - b.red).abs() < 1e-4
+        && (a.green - b.green).abs() < 1e-4
+        && (a.blue - b.blue).abs() < 1e-4
+}
+
+#[test]
+fn default_only_clamps() {
+    let post = PostProcess::default();
+    let color = Rgb::new(0.25, 0.5, 0.75);
+    assert!(close(post.process(color), color));
+    assert!(close(
+        post.process(Rgb::new(3.0, 0.5, 0.0)),
+        Rgb::new(1.0, 0.5, 0.0)
+    ));
+}
+
+#[test]
+fn tonemaps_keep_highlights_in_range() {
+    for tonemap in [Tonemap::Reinhard, Tonemap::Aces] {
+        let mut last = 0.0;
+        for c in [0.0, 0.1, 0.5, 1.0, 4.0, 100.0] {
+            let mapped = tonemap.map(c);
+            assert!(
+                (0.0..=1.0).contains(&mapped),
+                "{} maps {} to {}",
+                tonemap,
+                c,
+                mapped
+            );
+            assert!(mapped >= last, "{} isn't monotonic at {}", tonemap, c);
+            last = mapped;
+        }
+    }
+    assert!((Tonemap::Reinhard.map(1.0) - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn zero_saturation_is_gray() {
+    let post = PostProcess {
+        saturation: 0.0,
+        ..Default::default()
+    };
+    let gray = post.process(Rgb::new(0.9, 0.2, 0.1));
+    assert!((gray.red - gray.green).abs() < 1e-3 && (gray.green - gray.blue).abs() < 1e-3);
+}
+
+#[test]
+fn brightness_scales_linearly() {
+    let post = PostProcess {
+        brightness: 0.5,
+        ..Default::default()
+    };
+    assert!(close(
+        post.process(Rgb::new(1.0, 0.5, 0.0)),
+        Rgb::new(0.5, 0.25, 0.0)
+    ));
+}
+
+#[test]
+fn loads_from_params_file() {
+    let params = ParamFile::parse("[post]\ntonemap = \"aces\"\nbrightness = 0.6\n").unwrap();
+    let post = params.post();
+    assert_eq!(post.tonemap, Tonemap::Aces);
+    assert_eq!(post.brightness, 0.6);
+    assert_eq!(post.saturation, 1.0);
+
+    // gamma belongs to [output], so hardware only gets it once
+    let e = ParamFile::parse("[post]\ngamma = 2.2\n").unwrap_err();
+    assert!(e.to_string().contains("[output]"), "{}", e);
+    assert!(ParamFile::parse("[post]\ntonemap = \"filmic\"\n").is_err());
+    assert!(ParamFile::parse("[post]\nexposure = 2.0\n").is_err());
+}
@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use driver_examples::{
+    params::ParamFile,
+    power::{Injection, PowerConfig, PowerLimiter},
+};
+use palette::rgb::Rgb;
+use spatial_led::Sled;
+
+fn room() -> Sled<Rgb> {
+    let room = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("complex_room.yap");
+    Sled::new(room.to_str().unwrap()).unwrap()
+}
+
+fn white(sled: &Sled<Rgb>) -> Vec<Rgb> {
+    vec![Rgb::new(1.0, 1.0, 1.0); sled.num_leds()]
+}
+
+#[test]
+fn estimates_without_a_budget() {
+    let sled = room();
+    let mut limiter = PowerLimiter::new(PowerConfig::default(), &sled).unwrap();
+    let mut colors = white(&sled);
+    let estimate = limiter.apply(&mut colors);
+
+    let expected = sled.num_leds() as f32 * 61.0;
+    assert!((estimate.requested_ma - expected).abs() < 1.0);
+    assert_eq!(estimate.output_ma, estimate.requested_ma);
+    assert_eq!(estimate.scale, 1.0);
+    assert_eq!(colors, white(&sled));
+}
+
+#[test]
+fn stays_under_the_supply_budget() {
+    let sled = room();
+    let config = PowerConfig {
+        budget_ma: Some(10_000.0),
+        ..Default::default()
+    };
+    let mut limiter = PowerLimiter::new(config, &sled).unwrap();
+    let mut colors = white(&sled);
+    let estimate = limiter.apply(&mut colors);
+
+    assert!(estimate.scale < 1.0);
+    assert!(estimate.output_ma <= 10_000.0 + 1.0, "{:?}", estimate);
+    assert!(estimate.output_ma >= 10_000.0 - 1.0, "{:?}", estimate);
+    assert_eq!(limiter.last_estimate(), estimate);
+}
+
+#[test]
+fn injection_points_only_dim_their_own_leds() {
+    let sled = room();
+    let config = PowerConfig {
+        injections: vec![Injection {
+            leds: Some([0, 100]),
+            budget_ma: 1_000.0,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let mut limiter = PowerLimiter::new(config, &sled).unwrap();
+    let mut colors = white(&sled);
+    limiter.apply(&mut colors);
+
+    let zone = limiter.estimate_ma(&colors[..100]);
+    assert!(zone <= 1_000.0 + 1.0, "{}", zone);
+    assert!(colors[100..].iter().all(|c| *c == Rgb::new(1.0, 1.0, 1.0)));
+}
+
+#[test]
+fn injection_points_by_segment() {
+    let sled = room();
+    let config = PowerConfig {
+        injections: vec![Injection {
+            segments: vec![0],
+            budget_ma: 0.0,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let mut limiter = PowerLimiter::new(config, &sled).unwrap();
+    let mut colors = white(&sled);
+    limiter.apply(&mut colors);
+
+    for (led, color) in sled.leds().zip(&colors) {
+        let expected = match led.segment() {
+            0 => Rgb::new(0.0, 0.0, 0.0),
+            _ => Rgb::new(1.0, 1.0, 1.0),
+        };
+        assert_eq!(*color, expected);
+    }
+}
+
+#[test]
+fn rejects_bad_configs() {
+    let sled = room();
+    let config = PowerConfig {
+        injections: vec![Injection {
+            segments: vec![sled.num_segments()],
+            budget_ma: 1_000.0,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert!(PowerLimiter::new(config, &sled).is_err());
+
+    assert!(ParamFile::parse("[power]\nbudget_ma = -5.0\n").is_err());
+    let params = ParamFile::parse(
+        "[power]\nbudget_ma = 4000\n[[power.injections]]\nleds = [0, 10]\nbudget_ma = 500\n",
+    )
+    .unwrap();
+    assert_eq!(params.power().budget_ma, Some(4000.0));
+    assert_eq!(params.power().injections[0].leds, Some([0, 10]));
+}
@@ -0,0 +1,188 @@
+use std::{
+    net::{SocketAddr, UdpSocket},
+    thread,
+    time::{Duration, Instant},
+};
+
+use driver_examples::{
+    clock,
+    net::{
+        ddp,
+        input::{InputLayer, NetworkInput},
+        realtime::{RealtimeConfig, RealtimeInput, RealtimeProtocol, RealtimeSink},
+        wled,
+    },
+    params::ParamFile,
+    sink::{Frame, LedSink},
+};
+use palette::rgb::Rgb;
+use spatial_led::Vec2;
+
+const BLACK: Rgb = Rgb::new(0.0, 0.0, 0.0);
+
+/// Polls until `count` packets have been handled, since they can take a moment to
+/// arrive even over loopback.
+fn poll_for(input: &mut RealtimeInput, count: usize) {
+    let started = Instant::now();
+    let mut received = 0;
+    while received < count {
+        assert!(started.elapsed() < Duration::from_secs(2), "packets lost");
+        received += input.poll().unwrap();
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn replacing_input(protocol: RealtimeProtocol, leds: usize) -> (RealtimeInput, SocketAddr) {
+    let config = RealtimeConfig {
+        layer: InputLayer::Replace,
+        ..RealtimeConfig::default()
+    };
+    let mut input = RealtimeInput::new(config, &Default::default(), leds);
+    let address = input.bind(protocol, "127.0.0.1:0").unwrap();
+    (input, address)
+}
+
+/// Every LED gets its index as its red channel.
+fn ramp(leds: usize) -> Vec<Rgb> {
+    (0..leds)
+        .map(|i| Rgb::new((i % 256) as f32 / 255.0, 1.0, 0.0))
+        .collect()
+}
+
+#[test]
+fn ddp_splits_frames_and_pushes_the_last_packet() {
+    let data: Vec<u8> = (0..600 * 3).map(|i| i as u8).collect();
+    let packets = ddp::frame_packets(3, &data, false);
+    assert_eq!(packets.len(), 2);
+
+    assert_eq!(packets[0][..4], [0x40, 3, 0x0b, 1]);
+    assert_eq!(packets[0][4..8], 0u32.to_be_bytes());
+    assert_eq!(packets[0][8..10], 1440u16.to_be_bytes());
+    assert_eq!(packets[1][0], 0x41);
+    assert_eq!(packets[1][4..8], 1440u32.to_be_bytes());
+    assert_eq!(packets[1][8..10], 360u16.to_be_bytes());
+
+    let parsed = ddp::parse(&packets[1]).unwrap();
+    assert!(parsed.push && !parsed.white);
+    assert_eq!(parsed.offset, 1440);
+    assert_eq!(parsed.data, &data[1440..]);
+
+    // RGBW packets keep pixels whole
+    let packets = ddp::frame_packets(1, &[0; 400 * 4], true);
+    assert_eq!(packets[0][8..10], 1440u16.to_be_bytes());
+    assert_eq!(packets[0][2], 0x1b);
+}
+
+#[test]
+fn wled_uses_dnrgb_past_490_leds() {
+    let packets = wled::frame_packets(2, &[7; 100 * 3]);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0][..3], [2, 2, 7]);
+    assert_eq!(packets[0].len(), 2 + 300);
+
+    let packets = wled::frame_packets(2, &[7; 1000 * 3]);
+    assert_eq!(packets.len(), 3);
+    for (packet, start) in packets.iter().zip([0u16, 489, 978]) {
+        assert_eq!(packet[..2], [4, 2]);
+        assert_eq!(packet[2..4], start.to_be_bytes());
+    }
+    assert_eq!(packets[2].len(), 4 + 22 * 3);
+}
+
+#[test]
+fn round_trips_through_the_sink() {
+    for (protocol, leds) in [(RealtimeProtocol::Ddp, 600), (RealtimeProtocol::Wled, 600)] {
+        let (mut input, address) = replacing_input(protocol, leds);
+        let params = ParamFile::parse("[output]\ndither = false\n").unwrap();
+        let mut sink = RealtimeSink::new(
+            protocol,
+            &address.to_string(),
+            &params.realtime(),
+            &params.output(),
+        )
+        .unwrap();
+
+        let colors = ramp(leds);
+        let positions = vec![Vec2::ZERO; leds];
+        sink.send(&Frame::new(&colors, &positions)).unwrap();
+        poll_for(&mut input, 2);
+
+        let mut received = vec![BLACK; leds + 10];
+        input.apply(&mut received);
+        assert_eq!(received[..leds], colors, "{}", protocol);
+        assert_eq!(received[leds], BLACK);
+    }
+}
+
+#[test]
+fn ddp_waits_for_push() {
+    let (mut input, address) = replacing_input(RealtimeProtocol::Ddp, 4);
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    sender
+        .send_to(&ddp::packet(1, 3, &[255, 0, 0], false, false), address)
+        .unwrap();
+    poll_for(&mut input, 1);
+    let mut colors = vec![Rgb::new(0.0, 0.0, 1.0); 4];
+    input.apply(&mut colors);
+    assert_eq!(colors, vec![BLACK; 4]);
+
+    sender
+        .send_to(&ddp::packet(2, 6, &[0, 255, 0], false, true), address)
+        .unwrap();
+    poll_for(&mut input, 1);
+    input.apply(&mut colors);
+    assert_eq!(
+        colors,
+        [
+            BLACK,
+            Rgb::new(1.0, 0.0, 0.0),
+            Rgb::new(0.0, 1.0, 0.0),
+            BLACK
+        ]
+    );
+}
+
+#[test]
+fn wled_streams_time_out() {
+    // streams time out by the wall clock, even on a thread whose effect clock is frozen
+    clock::freeze();
+    let config = RealtimeConfig {
+        layer: InputLayer::Over,
+        ..RealtimeConfig::default()
+    };
+    let mut input = RealtimeInput::new(config, &Default::default(), 3);
+    let address = input.bind(RealtimeProtocol::Wled, "127.0.0.1:0").unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    // WARLS, setting only the LED at index 2
+    sender.send_to(&[1, 1, 2, 255, 255, 255], address).unwrap();
+    poll_for(&mut input, 1);
+    assert!(input.is_live());
+
+    let driver = Rgb::new(0.0, 0.0, 1.0);
+    let mut colors = vec![driver; 3];
+    input.apply(&mut colors);
+    assert_eq!(colors, [driver, driver, Rgb::new(1.0, 1.0, 1.0)]);
+
+    thread::sleep(Duration::from_millis(1100));
+    input.poll().unwrap();
+    assert!(!input.is_live());
+    let mut colors = vec![driver; 3];
+    input.apply(&mut colors);
+    assert_eq!(colors, vec![driver; 3]);
+    clock::unfreeze();
+}
+
+#[test]
+fn parses_realtime_settings() {
+    let params = ParamFile::parse("[realtime]\ntimeout = 0.2\nlayer = \"htp\"\n").unwrap();
+    assert_eq!(params.realtime().wled_timeout(), 1);
+    assert_eq!(params.realtime().layer, InputLayer::Htp);
+    assert_eq!(RealtimeConfig::default().wled_timeout(), 3);
+
+    assert!(ParamFile::parse("[realtime]\ntimeout = -1\n").is_err());
+    assert!(ParamFile::parse("[realtime]\nport = 4048\n").is_err());
+    assert_eq!("DDP".parse(), Ok(RealtimeProtocol::Ddp));
+    assert!("e131".parse::<RealtimeProtocol>().is_err());
+}
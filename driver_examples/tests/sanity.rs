@@ -0,0 +1,135 @@
+//! Property tests: every effect is stepped with random timesteps, from stalls to
+//! multi-second hitches, and must only ever produce finite, non-negative colors.
+//!
+//! "In range" means two things here. Effects draw in linear light and may go above 1
+//! where they add up, so their raw output only has to be finite and non-negative. Once
+//! post-processed, every channel has to land in `0..=1`, whichever tonemap is used.
+
+use std::{path::PathBuf, time::Duration};
+
+use driver_examples::{
+    clock,
+    post::{PostProcess, Tonemap},
+    registry, rng,
+    sanitize::{SanitizeMode, Sanitizer},
+};
+use palette::rgb::Rgb;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use spatial_led::Sled;
+
+const RUNS: u64 = 4;
+const FRAMES: usize = 150;
+
+fn random_timestep(rng: &mut impl Rng) -> Duration {
+    let secs = match rng.gen_range(0..10) {
+        0 => 0.0,
+        1 => rng.gen_range(0.0..1e-5),
+        2 => rng.gen_range(0.04..3.0),
+        _ => rng.gen_range(0.001..0.04),
+    };
+    Duration::from_secs_f64(secs)
+}
+
+fn check(effect: &str) {
+    let info = registry::find(effect).unwrap();
+    let room = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("complex_room.yap");
+    let sled = Sled::<Rgb>::new(room.to_str().unwrap()).unwrap();
+    let sanitizer = Sanitizer::new(SanitizeMode::Error);
+    let posts = [Tonemap::None, Tonemap::Reinhard, Tonemap::Aces].map(|tonemap| PostProcess {
+        tonemap,
+        ..PostProcess::default()
+    });
+
+    for seed in 0..RUNS {
+        let mut timesteps = StdRng::seed_from_u64(seed);
+        clock::freeze();
+        let mut driver = info.build_driver();
+        rng::set_seed(driver.data_mut(), seed);
+        driver.mount(sled.clone());
+
+        for frame in 0..FRAMES {
+            let step = random_timestep(&mut timesteps);
+            clock::advance(step);
+            driver.step();
+
+            let mut colors: Vec<Rgb> = driver.colors().copied().collect();
+            if let Err(e) = sanitizer.apply(&mut colors) {
+                panic!(
+                    "{} (seed {}, frame {}, step {:?}): {}",
+                    effect, seed, frame, step, e
+                );
+            }
+            for post in &posts {
+                for (i, color) in colors.iter().enumerate() {
+                    let color = post.process(*color);
+                    assert!(
+                        [color.red, color.green, color.blue]
+                            .iter()
+                            .all(|c| (0.0..=1.0).contains(c)),
+                        "{} (seed {}, frame {}, {:?}): LED {} is out of range: {:?}",
+                        effect,
+                        seed,
+                        frame,
+                        post.tonemap,
+                        i,
+                        color
+                    );
+                }
+            }
+        }
+    }
+}
+
+macro_rules! sanity_tests {
+    ($($effect:ident),*) => {
+        $(
+            #[test]
+            fn $effect() {
+                check(stringify!($effect));
+            }
+        )*
+
+        #[test]
+        fn every_effect_is_checked() {
+            let tested = [$(stringify!($effect)),*];
+            for name in registry::names() {
+                assert!(tested.contains(&name), "no sanity test for `{}`", name);
+            }
+        }
+    };
+}
+
+sanity_tests!(comet, embers, ripples, scan, warpspeed);
+
+#[test]
+fn clamp_fixes_each_channel() {
+    let mut colors = [
+        Rgb::new(f32::NAN, f32::INFINITY, -0.5),
+        Rgb::new(2.0, 0.5, 0.0),
+    ];
+    Sanitizer::new(SanitizeMode::Clamp)
+        .apply(&mut colors)
+        .unwrap();
+    assert_eq!(colors, [Rgb::new(0.0, 1.0, 0.0), Rgb::new(2.0, 0.5, 0.0)]);
+}
+
+#[test]
+fn replace_swaps_whole_leds() {
+    let mut sanitizer = Sanitizer::new(SanitizeMode::Replace);
+    sanitizer.replacement = Rgb::new(1.0, 0.0, 1.0);
+    let mut colors = [
+        Rgb::new(0.2, f32::NEG_INFINITY, 0.2),
+        Rgb::new(0.2, 0.2, 0.2),
+    ];
+    sanitizer.apply(&mut colors).unwrap();
+    assert_eq!(colors, [Rgb::new(1.0, 0.0, 1.0), Rgb::new(0.2, 0.2, 0.2)]);
+}
+
+#[test]
+fn error_names_the_led() {
+    let mut colors = [Rgb::new(0.0, 0.0, 0.0), Rgb::new(0.0, f32::NAN, 0.0)];
+    let e = Sanitizer::new(SanitizeMode::Error)
+        .apply(&mut colors)
+        .unwrap_err();
+    assert!(e.to_string().contains("LED 1"), "{}", e);
+}
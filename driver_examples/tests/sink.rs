@@ -0,0 +1,100 @@
+use std::{cell::RefCell, rc::Rc};
+
+use driver_examples::sink::{read_recording, FanOut, Frame, LedSink, Recorder};
+use palette::rgb::Rgb;
+use spatial_led::{SledError, SledResult, Vec2};
+
+type Frames = Vec<Vec<(Rgb, Vec2)>>;
+
+/// Keeps every frame it's sent, shared so the test can look at them after handing the
+/// sink off.
+#[derive(Clone, Default)]
+struct Collect(Rc<RefCell<Frames>>);
+
+impl LedSink for Collect {
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        self.0
+            .borrow_mut()
+            .push(frame.colors_and_positions().collect());
+        Ok(())
+    }
+}
+
+struct Fail;
+
+impl LedSink for Fail {
+    fn send(&mut self, _frame: &Frame) -> SledResult {
+        SledError::from("unplugged").as_err()
+    }
+}
+
+fn frame_data() -> (Vec<Rgb>, Vec<Vec2>) {
+    let colors = vec![
+        Rgb::new(1.0, 0.5, 0.0),
+        Rgb::new(0.0, 0.0, 0.0),
+        Rgb::new(2.0, -1.0, f32::NAN),
+    ];
+    let positions = vec![
+        Vec2::new(0.0, 0.0),
+        Vec2::new(1.0, 0.0),
+        Vec2::new(1.0, 1.0),
+    ];
+    (colors, positions)
+}
+
+#[test]
+fn fan_out_reaches_every_sink() {
+    let (colors, positions) = frame_data();
+    let (first, second) = (Collect::default(), Collect::default());
+    let mut outputs = FanOut::new()
+        .with_sink(first.clone())
+        .with_sink(Fail)
+        .with_sink(second.clone());
+    assert_eq!(outputs.len(), 3);
+
+    // a failing sink doesn't keep the frame from the ones after it
+    let result = outputs.send(&Frame::new(&colors, &positions));
+    assert!(result.is_err());
+
+    let expected: Vec<_> = colors
+        .iter()
+        .copied()
+        .zip(positions.iter().copied())
+        .collect();
+    for sink in [first, second] {
+        let frames = sink.0.borrow();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0][..2], expected[..2]);
+        assert_eq!(frames[0][2].1, positions[2]);
+    }
+}
+
+#[test]
+fn recordings_read_back() {
+    let (colors, positions) = frame_data();
+    let mut recorder = Recorder::new(vec![]);
+    recorder.send(&Frame::new(&colors, &positions)).unwrap();
+    recorder.send(&Frame::new(&colors, &positions)).unwrap();
+    assert!(recorder
+        .send(&Frame::new(&colors[..2], &positions))
+        .is_err());
+
+    let written = recorder.into_inner();
+    let text = String::from_utf8(written.clone()).unwrap();
+    assert_eq!(text.lines().nth(1), Some("ff8000 000000 ff0000"));
+
+    let frames = read_recording(written.as_slice()).unwrap();
+    assert_eq!(frames, vec![vec![[255, 128, 0], [0, 0, 0], [255, 0, 0]]; 2]);
+}
+
+#[test]
+fn rejects_broken_recordings() {
+    assert!(read_recording("".as_bytes()).is_err());
+    assert!(read_recording("ff0000\n".as_bytes()).is_err());
+    assert!(read_recording("sled-recording 1 2\nff0000\n".as_bytes()).is_err());
+    assert!(read_recording("sled-recording 1 1\nzz0000\n".as_bytes()).is_err());
+    assert_eq!(
+        read_recording("sled-recording 1 1\n0000ff\n".as_bytes()).unwrap(),
+        vec![vec![[0, 0, 255]]]
+    );
+}
@@ -0,0 +1,95 @@
+use driver_examples::{
+    encode::{Encoder, EncoderConfig, Gamma},
+    params::ParamFile,
+    strip::{extract_white, kelvin_to_rgb, ColorOrder, StripConfig, StripType},
+};
+use palette::rgb::Rgb;
+
+fn close(a: Rgb, b: Rgb) -> bool {
+    (a.red - b.red).abs() < 1e-4
+        && (a.green - b.green).abs() < 1e-4
+        && (a.blue - b.blue).abs() < 1e-4
+}
+
+#[test]
+fn white_gets_warmer_as_temperature_drops() {
+    let daylight = kelvin_to_rgb(6600.0);
+    assert!(close(daylight, Rgb::new(1.0, 1.0, 1.0)), "{:?}", daylight);
+
+    let warm = kelvin_to_rgb(3000.0);
+    let neutral = kelvin_to_rgb(4000.0);
+    assert_eq!(warm.red, 1.0);
+    assert!(warm.blue < neutral.blue && neutral.blue < 1.0);
+    assert!(kelvin_to_rgb(1000.0).blue == 0.0);
+}
+
+#[test]
+fn extraction_moves_the_shared_part_onto_white() {
+    let white = Rgb::new(1.0, 0.8, 0.6);
+
+    // exactly the white led's color goes entirely to white
+    let (rest, w) = extract_white(Rgb::new(0.5, 0.4, 0.3), white);
+    assert!(close(rest, Rgb::new(0.0, 0.0, 0.0)), "{:?}", rest);
+    assert!((w - 0.5).abs() < 1e-5);
+
+    // the limiting channel ends at 0, the others keep what white can't show
+    let (rest, w) = extract_white(Rgb::new(1.0, 1.0, 0.3), white);
+    assert!((w - 0.5).abs() < 1e-5);
+    assert!(close(rest, Rgb::new(0.5, 0.6, 0.0)), "{:?}", rest);
+
+    // pure colors don't use white at all
+    let (rest, w) = extract_white(Rgb::new(0.0, 0.7, 0.0), white);
+    assert_eq!(w, 0.0);
+    assert!(close(rest, Rgb::new(0.0, 0.7, 0.0)));
+}
+
+#[test]
+fn extraction_never_goes_past_full_white() {
+    let (rest, w) = extract_white(Rgb::new(2.0, 2.0, 2.0), Rgb::new(1.0, 1.0, 1.0));
+    assert_eq!(w, 1.0);
+    assert!(close(rest, Rgb::new(1.0, 1.0, 1.0)));
+}
+
+#[test]
+fn encoder_fills_the_white_channel() {
+    let config = EncoderConfig {
+        gamma: Gamma::Uniform(1.0),
+        dither: false,
+    };
+    let colors = [Rgb::new(1.0, 1.0, 1.0), Rgb::new(1.0, 0.5, 0.0)];
+
+    let mut rgb = Encoder::new(&config);
+    assert_eq!(
+        rgb.encode(colors.iter()),
+        &[[255, 255, 255, 0], [255, 128, 0, 0]]
+    );
+
+    let mut rgbw = Encoder::new(&config).with_white(Some(Rgb::new(1.0, 1.0, 1.0)));
+    assert_eq!(
+        rgbw.encode(colors.iter()),
+        &[[0, 0, 0, 255], [255, 128, 0, 0]]
+    );
+}
+
+#[test]
+fn loads_from_params_file() {
+    let params = ParamFile::default();
+    assert_eq!(params.strip(), StripConfig::default());
+    assert_eq!(params.strip().white(), None);
+
+    let params = ParamFile::parse(
+        "[strip]\ntype = \"sk6812w\"\norder = \"grb\"\nwhite_temperature = 3000\n",
+    )
+    .unwrap();
+    let strip = params.strip();
+    assert_eq!(strip.strip_type, StripType::Sk6812w);
+    assert_eq!(strip.order(), ColorOrder::Grb);
+    assert_eq!(strip.white(), Some(kelvin_to_rgb(3000.0)));
+
+    let params = ParamFile::parse("[strip]\ntype = \"ws2812\"\n").unwrap();
+    assert_eq!(params.strip().order(), ColorOrder::Grb);
+
+    assert!(ParamFile::parse("[strip]\ntype = \"apa102\"\n").is_err());
+    assert!(ParamFile::parse("[strip]\norder = \"rgbw\"\n").is_err());
+    assert!(ParamFile::parse("[strip]\nwhite_temperature = 100\n").is_err());
+}
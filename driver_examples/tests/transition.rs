@@ -0,0 +1,105 @@
+use std::{path::PathBuf, time::Duration};
+
+use driver_examples::{
+    clock::{self, Driver},
+    transition::{Blend, Crossfader},
+};
+use palette::{rgb::Rgb, IntoColor, Oklab};
+use spatial_led::Sled;
+
+const RED: Rgb = Rgb::new(1.0, 0.0, 0.0);
+const BLUE: Rgb = Rgb::new(0.0, 0.0, 1.0);
+const BLACK: Rgb = Rgb::new(0.0, 0.0, 0.0);
+const WHITE: Rgb = Rgb::new(1.0, 1.0, 1.0);
+
+fn assert_close(actual: Rgb, expected: Rgb) {
+    let close = [
+        (actual.red, expected.red),
+        (actual.green, expected.green),
+        (actual.blue, expected.blue),
+    ]
+    .iter()
+    .all(|(a, e)| (a - e).abs() < 1e-4);
+    assert!(close, "{:?} isn't {:?}", actual, expected);
+}
+
+/// A driver that lights every LED `color`.
+fn solid(color: Rgb) -> Driver<Rgb> {
+    let mut driver = Driver::new();
+    driver.set_draw_commands(move |sled, _, _| {
+        sled.set_all(color);
+        Ok(())
+    });
+    driver
+}
+
+fn room() -> Sled<Rgb> {
+    let room = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("complex_room.yap");
+    Sled::new(room.to_str().unwrap()).unwrap()
+}
+
+#[test]
+fn every_blend_starts_and_ends_on_its_colors() {
+    for blend in [Blend::Linear, Blend::Oklab, Blend::DipToBlack] {
+        assert_close(blend.mix(RED, BLUE, 0.0), RED);
+        assert_close(blend.mix(RED, BLUE, 1.0), BLUE);
+    }
+}
+
+#[test]
+fn linear_midpoint_averages_each_channel() {
+    assert_close(Blend::Linear.mix(RED, BLUE, 0.5), Rgb::new(0.5, 0.0, 0.5));
+    assert_close(
+        Blend::Linear.mix(BLACK, WHITE, 0.25),
+        Rgb::new(0.25, 0.25, 0.25),
+    );
+}
+
+#[test]
+fn oklab_midpoint_is_halfway_in_lightness() {
+    let mid: Oklab = Blend::Oklab.mix(BLACK, WHITE, 0.5).into_color();
+    assert!((mid.l - 0.5).abs() < 1e-3, "{:?}", mid);
+
+    let from: Oklab = RED.into_color();
+    let to: Oklab = BLUE.into_color();
+    let mid: Oklab = Blend::Oklab.mix(RED, BLUE, 0.5).into_color();
+    assert!((mid.l - (from.l + to.l) / 2.0).abs() < 1e-3, "{:?}", mid);
+    assert!((mid.a - (from.a + to.a) / 2.0).abs() < 1e-3, "{:?}", mid);
+    assert!((mid.b - (from.b + to.b) / 2.0).abs() < 1e-3, "{:?}", mid);
+}
+
+#[test]
+fn dip_to_black_is_dark_at_the_midpoint() {
+    assert_close(Blend::DipToBlack.mix(RED, BLUE, 0.5), BLACK);
+    // the old colors fade out over the first half, the new ones in over the second
+    assert_close(
+        Blend::DipToBlack.mix(RED, BLUE, 0.25),
+        Rgb::new(0.5, 0.0, 0.0),
+    );
+    assert_close(
+        Blend::DipToBlack.mix(RED, BLUE, 0.75),
+        Rgb::new(0.0, 0.0, 0.5),
+    );
+}
+
+#[test]
+fn crossfader_blends_over_its_duration() {
+    clock::freeze();
+    let mut fader = Crossfader::new(room(), solid(RED), Blend::Linear, Duration::from_secs(2));
+    fader.step();
+    fader.output().iter().for_each(|&c| assert_close(c, RED));
+
+    fader.switch_to(solid(BLUE));
+    clock::advance(Duration::from_secs(1));
+    fader.step();
+    assert!(fader.is_transitioning());
+    fader
+        .output()
+        .iter()
+        .for_each(|&c| assert_close(c, Rgb::new(0.5, 0.0, 0.5)));
+
+    clock::advance(Duration::from_secs(1));
+    fader.step();
+    assert!(!fader.is_transitioning());
+    fader.output().iter().for_each(|&c| assert_close(c, BLUE));
+}
@@ -0,0 +1,204 @@
+use std::path::PathBuf;
+
+use driver_examples::{
+    params::ParamFile,
+    strip::{ColorOrder, StripConfig, StripType},
+    wiring::{ChannelConfig, Wiring, WiringConfig},
+};
+use palette::rgb::Rgb;
+use spatial_led::Sled;
+
+fn room() -> Sled<Rgb> {
+    let room = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("complex_room.yap");
+    Sled::new(room.to_str().unwrap()).unwrap()
+}
+
+fn segment_leds(sled: &Sled<Rgb>, segment: usize) -> Vec<usize> {
+    sled.leds()
+        .filter(|led| led.segment() as usize == segment)
+        .map(|led| led.index() as usize)
+        .collect()
+}
+
+fn some(leds: Vec<usize>) -> Vec<Option<usize>> {
+    leds.into_iter().map(Some).collect()
+}
+
+#[test]
+fn defaults_to_one_line_on_pin_18() {
+    let sled = room();
+    let wiring = Wiring::new(&WiringConfig::default(), &StripConfig::default(), &sled).unwrap();
+
+    let [channel] = wiring.channels() else {
+        panic!("expected a single channel");
+    };
+    assert_eq!(channel.pin, 18);
+    assert_eq!(
+        channel.pixels,
+        (0..sled.num_leds()).map(Some).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn splits_the_room_across_lines() {
+    let sled = room();
+    assert!(sled.num_segments() >= 3);
+    let split = segment_leds(&sled, 2)[0];
+
+    let params = ParamFile::parse(&format!(
+        "[strip]\ntype = \"ws2812\"\n\n\
+         [[wiring.channels]]\npin = 18\nsegments = [1, 0]\n\n\
+         [[wiring.channels]]\npin = 13\nleds = [{}, {}]\ntype = \"sk6812w\"\n",
+        split,
+        sled.num_leds()
+    ))
+    .unwrap();
+    let wiring = Wiring::new(&params.wiring(), &params.strip(), &sled).unwrap();
+    let [first, second] = wiring.channels() else {
+        panic!("expected two channels");
+    };
+
+    let mut expected = segment_leds(&sled, 1);
+    expected.extend(segment_leds(&sled, 0));
+    assert_eq!(first.pin, 18);
+    assert_eq!(first.pixels, some(expected));
+    assert_eq!(first.strip.strip_type, StripType::Ws2812);
+
+    assert_eq!(second.pin, 13);
+    assert_eq!(second.pixels, some((split..sled.num_leds()).collect()));
+    assert_eq!(second.strip.strip_type, StripType::Sk6812w);
+    assert!(second.strip.white().is_some());
+
+    let colors: Vec<Rgb> = (0..sled.num_leds())
+        .map(|i| Rgb::new(i as f32, 0.0, 0.0))
+        .collect();
+    let sent: Vec<usize> = second.colors(&colors).map(|c| c.red as usize).collect();
+    assert_eq!(sent, (split..sled.num_leds()).collect::<Vec<_>>());
+}
+
+#[test]
+fn lines_override_the_strip() {
+    let base = StripConfig {
+        order: Some(ColorOrder::Bgr),
+        ..StripConfig::default()
+    };
+    let channel = ChannelConfig {
+        pin: 18,
+        white_temperature: Some(3000.0),
+        ..ChannelConfig::default()
+    };
+    let strip = channel.strip(&base);
+    assert_eq!(strip.order(), ColorOrder::Bgr);
+    assert_eq!(strip.white_temperature, 3000.0);
+
+    // a new type still takes [strip]'s order, unless the line sets its own
+    let channel = ChannelConfig {
+        strip_type: Some(StripType::Sk6812w),
+        ..channel
+    };
+    assert_eq!(channel.strip(&base).order(), ColorOrder::Bgr);
+    let channel = ChannelConfig {
+        order: Some(ColorOrder::Rgb),
+        ..channel
+    };
+    assert_eq!(channel.strip(&base).order(), ColorOrder::Rgb);
+}
+
+#[test]
+fn follows_the_physical_chain() {
+    let sled = room();
+    let (zero, one) = (segment_leds(&sled, 0), segment_leds(&sled, 1));
+    let start = one[3];
+
+    let params = ParamFile::parse(&format!(
+        "[[wiring.channels]]\npin = 18\nsegments = [0, 1]\nreversed = [1]\n\
+         start = {}\ndead = [[0, 1], [3, 2]]\n",
+        start
+    ))
+    .unwrap();
+    let wiring = Wiring::new(&params.wiring(), &params.strip(), &sled).unwrap();
+    let [channel] = wiring.channels() else {
+        panic!("expected a single channel");
+    };
+
+    // segment 1 runs backwards, and the chain enters it at `start`
+    let mut chain: Vec<usize> = zero.iter().chain(one.iter().rev()).copied().collect();
+    let offset = chain.iter().position(|&led| led == start).unwrap();
+    chain.rotate_left(offset);
+
+    let mut expected = some(chain);
+    expected.insert(0, None);
+    expected.splice(3..3, [None, None]);
+    assert_eq!(channel.pixels, expected);
+    assert_eq!(channel.pixels.len(), zero.len() + one.len() + 3);
+
+    let colors = vec![Rgb::new(1.0, 1.0, 1.0); sled.num_leds()];
+    let lit: Vec<bool> = channel.colors(&colors).map(|c| c.red > 0.0).collect();
+    assert_eq!(&lit[..6], &[false, true, true, false, false, true]);
+}
+
+#[test]
+fn rejects_wiring_outside_the_room() {
+    let sled = room();
+    let params = ParamFile::parse("[[wiring.channels]]\npin = 18\nsegments = [99]\n").unwrap();
+    assert!(Wiring::new(&params.wiring(), &params.strip(), &sled).is_err());
+
+    let params = ParamFile::parse("[[wiring.channels]]\npin = 18\nleds = [0, 100000]\n").unwrap();
+    assert!(Wiring::new(&params.wiring(), &params.strip(), &sled).is_err());
+
+    for channel in [
+        "segments = [0]\nreversed = [1]",
+        "segments = [0]\nstart = 100000",
+        "leds = [0, 10]\ndead = [[11, 1]]",
+    ] {
+        let params =
+            ParamFile::parse(&format!("[[wiring.channels]]\npin = 18\n{}\n", channel)).unwrap();
+        assert!(
+            Wiring::new(&params.wiring(), &params.strip(), &sled).is_err(),
+            "{}",
+            channel
+        );
+    }
+
+    assert!(ParamFile::parse("[[wiring.channels]]\nsegments = [0]\n").is_err());
+    assert!(ParamFile::parse("[[wiring.channels]]\npin = 18\nbogus = 1\n").is_err());
+}
+
+#[test]
+fn rejects_leds_wired_twice() {
+    for wiring in [
+        "[[wiring.channels]]\npin = 18\nsegments = [0, 0]\n",
+        "[[wiring.channels]]\npin = 18\nsegments = [0]\n\
+         [[wiring.channels]]\npin = 13\nsegments = [0]\n",
+        "[[wiring.channels]]\npin = 18\nleds = [0, 10]\n\
+         [[wiring.channels]]\npin = 13\nleds = [5, 20]\n",
+    ] {
+        assert!(ParamFile::parse(wiring).is_err(), "{}", wiring);
+    }
+
+    // a range over a segment only shows up against the room
+    let sled = room();
+    let first = segment_leds(&sled, 1)[0];
+    let params = ParamFile::parse(&format!(
+        "[[wiring.channels]]\npin = 18\nsegments = [1]\nleds = [{}, {}]\n",
+        first,
+        first + 1
+    ))
+    .unwrap();
+    let e = Wiring::new(&params.wiring(), &params.strip(), &sled).unwrap_err();
+    assert!(e.to_string().contains(&format!("Led {}", first)), "{}", e);
+}
+
+#[test]
+fn rejects_pins_off_the_pwm_channel() {
+    assert!(ParamFile::parse("[[wiring.channels]]\npin = 12\n").is_ok());
+    assert!(ParamFile::parse("[[wiring.channels]]\npin = 13\n").is_err());
+    assert!(ParamFile::parse(
+        "[[wiring.channels]]\npin = 18\n[[wiring.channels]]\npin = 19\n\
+         [[wiring.channels]]\npin = 21\n"
+    )
+    .is_err());
+    assert!(
+        ParamFile::parse("[[wiring.channels]]\npin = 18\n[[wiring.channels]]\npin = 12\n").is_err()
+    );
+}
@@ -0,0 +1,65 @@
+use driver_examples::{
+    strip::{ColorOrder, StripConfig},
+    wiring::Channel,
+};
+use rs_ws281x::{ChannelBuilder, Controller, ControllerBuilder, RawColor, Result};
+
+/// rs_ws281x drives one line from each of the two PWM channels.
+pub const MAX_CHANNELS: usize = 2;
+
+/// The parts of `rs_ws281x::Controller` the runtime uses, so it can run against a
+/// `MockController` instead of real hardware.
+pub trait LedController {
+    /// The LEDs of a channel, each as `0xWWRRGGBB` in native byte order.
+    fn leds_mut(&mut self, channel: usize) -> &mut [RawColor];
+
+    fn render(&mut self) -> Result<()>;
+}
+
+impl LedController for Controller {
+    fn leds_mut(&mut self, channel: usize) -> &mut [RawColor] {
+        Controller::leds_mut(self, channel)
+    }
+
+    fn render(&mut self) -> Result<()> {
+        Controller::render(self)
+    }
+}
+
+/// The rs_ws281x strip type that sends channels in the configured order. All the RGB
+/// chips share one protocol, so they only differ by order.
+pub fn strip_type(strip: &StripConfig) -> rs_ws281x::StripType {
+    use rs_ws281x::StripType::*;
+
+    match (strip.strip_type.has_white(), strip.order()) {
+        (false, ColorOrder::Rgb) => Ws2811Rgb,
+        (false, ColorOrder::Rbg) => Ws2811Rbg,
+        (false, ColorOrder::Grb) => Ws2811Grb,
+        (false, ColorOrder::Gbr) => Ws2811Gbr,
+        (false, ColorOrder::Brg) => Ws2811Brg,
+        (false, ColorOrder::Bgr) => Ws2811Bgr,
+        (true, ColorOrder::Rgb) => Sk6812Rgbw,
+        (true, ColorOrder::Rbg) => Sk6812Rbgw,
+        (true, ColorOrder::Grb) => Sk6812Grbw,
+        (true, ColorOrder::Gbr) => Sk6812Gbrw,
+        (true, ColorOrder::Brg) => Sk6812Brgw,
+        (true, ColorOrder::Bgr) => Sk6812Bgrw,
+    }
+}
+
+/// Sets up the Pi's hardware for these lines, in order.
+pub fn construct_gpio_controller(channels: &[Channel]) -> Result<Controller> {
+    let mut builder = ControllerBuilder::new();
+    for (i, channel) in channels.iter().enumerate() {
+        builder.channel(
+            i,
+            ChannelBuilder::new()
+                .pin(channel.pin as i32)
+                .count(channel.pixels.len() as i32)
+                .strip_type(strip_type(&channel.strip))
+                .brightness(255)
+                .build(),
+        );
+    }
+    builder.build()
+}
@@ -1,3 +0,0 @@
-pub mod comet;
-pub mod ripples;
-pub mod warpspeed;
\ No newline at end of file
@@ -0,0 +1,4 @@
+pub mod controller;
+pub mod mock;
+pub mod runtime;
+pub mod sink;
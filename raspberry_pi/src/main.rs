@@ -1,53 +1,238 @@
-use rs_ws281x::{ChannelBuilder, Controller, ControllerBuilder};
+use driver_examples::{
+    net::{
+        dmx::DmxProtocol,
+        input::{DmxInput, NetworkInput},
+        realtime::{RealtimeInput, RealtimeProtocol},
+    },
+    params::ParamFile,
+    playlist::Playlist,
+    power::PowerLimiter,
+    registry,
+    sink::LedSink,
+    transition::{Blend, Crossfader},
+    wiring::Wiring,
+};
 use palette::rgb::Rgb;
+use raspberry_pi::{
+    controller::{construct_gpio_controller, MAX_CHANNELS},
+    mock::MockController,
+    runtime::Runtime,
+    sink::GpioSink,
+};
 use spatial_led::Sled;
-mod effects;
+
+use std::{
+    io::{self, BufRead},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+const PARAMS_PATH: &str = "./params.toml";
+const TRANSITION: Duration = Duration::from_secs(2);
+const POWER_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+/// How long to wait before trying again after a frame fails.
+const RETRY_DELAY: Duration = Duration::from_millis(100);
 
 fn main() {
     let sled = Sled::<Rgb>::new("./config.yap").unwrap();
     let num_leds = sled.num_leds();
     println!("Starting SLED system of {} LEDs.", num_leds);
 
-    let mut driver = effects::ripples::build_driver();
-    driver.mount(sled);
+    // per-room tuning, see driver_examples::params for the format
+    let params = match std::path::Path::new(PARAMS_PATH).exists() {
+        true => ParamFile::load(PARAMS_PATH).unwrap(),
+        false => ParamFile::default(),
+    };
+    let power = PowerLimiter::new(params.power(), &sled).unwrap();
+    let wiring = Wiring::new(&params.wiring(), &params.strip(), &sled).unwrap();
+    if wiring.channels().len() > MAX_CHANNELS {
+        eprintln!(
+            "[wiring] has {} channels, but the Pi can only drive {}.",
+            wiring.channels().len(),
+            MAX_CHANNELS
+        );
+        return;
+    }
 
-    println!("Ripples effect Running. Press CTRL+C to quit.");
+    // either `raspberry_pi <effect>` or `raspberry_pi --playlist <file>`, with `--mock`
+    // anywhere to render to a mock controller instead of GPIO, and any number of
+    // `--listen <protocol>` to take pixels from the network
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mock = args.iter().any(|arg| arg == "--mock");
+    args.retain(|arg| arg != "--mock");
+    let inputs = match listen(&mut args, &params, num_leds) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let (fader, playlist) = match args.as_slice() {
+        [flag, path] if flag == "--playlist" => {
+            let mut playlist = Playlist::load(path).unwrap();
+            if let Some(seed) = params.seed() {
+                playlist.reseed(seed);
+            }
+            println!(
+                "Playing {} effects from {}.",
+                playlist.entries().len(),
+                path
+            );
+            (playlist.start(sled, &params).unwrap(), Some(playlist))
+        }
+        _ => {
+            let effect_name = args.first().map_or("ripples", String::as_str);
+            let Some(effect) = registry::find(effect_name) else {
+                let names: Vec<_> = registry::names().collect();
+                eprintln!(
+                    "Unknown effect `{}`. Available: {}",
+                    effect_name,
+                    names.join(", ")
+                );
+                return;
+            };
 
-    let mut gpio_controller = construct_gpio_controller(num_leds);
-    loop {
-        driver.step();
-        let colors = driver.colors();
-        update_gpio(&mut gpio_controller, colors);
+            let driver = effect.build_driver_with(&params).unwrap();
+            println!("{} effect Running.", effect.name);
+            (
+                Crossfader::new(sled, driver, Blend::Oklab, TRANSITION),
+                None,
+            )
+        }
+    };
+
+    println!("Type an effect's name and press enter to switch to it. Press CTRL+C to quit.");
+
+    match mock {
+        true => {
+            let controller = MockController::new(wiring.channels()).keep_last(1);
+            println!(
+                "Rendering to a mock controller, {:.1} ms per frame.",
+                controller.transfer_time().as_secs_f32() * 1000.0
+            );
+            let sink = GpioSink::new(controller, wiring, &params);
+            run(Runtime::new(fader, playlist, params, power, sink), inputs);
+        }
+        false => {
+            let controller = construct_gpio_controller(wiring.channels()).unwrap();
+            let sink = GpioSink::new(controller, wiring, &params);
+            run(Runtime::new(fader, playlist, params, power, sink), inputs);
+        }
     }
 }
 
-fn construct_gpio_controller(num_leds: usize) -> Controller {
-    ControllerBuilder::new()
-        .channel(
-            0,
-            ChannelBuilder::new()
-                .pin(18)
-                .count(num_leds as i32)
-                .strip_type(rs_ws281x::StripType::Ws2811Gbr)
-                .brightness(255)
-                .build(),
-        )
-        .build()
-        .unwrap()
+fn run<S: LedSink>(mut runtime: Runtime<S>, inputs: Vec<Box<dyn NetworkInput>>) {
+    for input in inputs {
+        runtime = runtime.with_input(input);
+    }
+    let requests = read_effect_requests();
+    let mut last_power_report = Instant::now();
+    let mut last_error = None;
+    loop {
+        if let Ok(name) = requests.try_recv() {
+            if let Err(e) = runtime.switch_to(&name) {
+                eprintln!("{}", e);
+            }
+        }
+
+        // a full socket buffer or an interface going down shouldn't take the lights
+        // with it, so report the error once and keep trying
+        let step = match runtime.step() {
+            Ok(step) => step,
+            Err(e) => {
+                let message = e.to_string();
+                if last_error.as_ref() != Some(&message) {
+                    eprintln!("Frame failed, retrying: {}", message);
+                    last_error = Some(message);
+                }
+                thread::sleep(RETRY_DELAY);
+                continue;
+            }
+        };
+        if last_error.take().is_some() {
+            println!("Frames are going out again.");
+        }
+        if let Some(effect) = step.started {
+            println!("Now playing {}.", effect.name);
+        }
+
+        let estimate = step.estimate;
+        if last_power_report.elapsed() >= POWER_REPORT_INTERVAL {
+            match estimate.scale < 1.0 {
+                true => println!(
+                    "Drawing {:.0} mA, limited from {:.0} mA.",
+                    estimate.output_ma, estimate.requested_ma
+                ),
+                false => println!("Drawing {:.0} mA.", estimate.output_ma),
+            }
+            last_power_report = Instant::now();
+        }
+    }
 }
 
-fn update_gpio<'a>(controller: &mut Controller, colors: impl Iterator<Item = &'a Rgb>) {
-    let leds = controller.leds_mut(0);
-
-    let mut i = 0;
-    for color in colors {
-        leds[i] = [
-            (color.red * 255.0) as u8,
-            (color.green * 255.0) as u8,
-            (color.blue * 255.0) as u8,
-            0,
-        ];
-        i += 1;
+/// Takes every `--listen <protocol>` pair out of `args`, listening for Art-Net and sACN
+/// universes laid out as in `[dmx]`, and DDP and WLED streams as set in `[realtime]`.
+fn listen(
+    args: &mut Vec<String>,
+    params: &ParamFile,
+    num_leds: usize,
+) -> Result<Vec<Box<dyn NetworkInput>>, String> {
+    let mut dmx = None;
+    let mut realtime = None;
+    while let Some(at) = args.iter().position(|arg| arg == "--listen") {
+        let name = args
+            .get(at + 1)
+            .ok_or("--listen needs a protocol: artnet, sacn, ddp or wled.")?;
+        if let Ok(protocol) = name.parse::<DmxProtocol>() {
+            let input = dmx.get_or_insert_with(|| DmxInput::new(params.dmx(), &params.output()));
+            input
+                .listen(protocol, num_leds)
+                .map_err(|e| format!("Failed to listen for {}: {}", protocol, e))?;
+            let universes: Vec<u16> = input.universes(num_leds).collect();
+            println!(
+                "Listening for {} on universes {} to {}.",
+                protocol,
+                universes.first().unwrap_or(&0),
+                universes.last().unwrap_or(&0)
+            );
+        } else if let Ok(protocol) = name.parse::<RealtimeProtocol>() {
+            let input = realtime.get_or_insert_with(|| {
+                RealtimeInput::new(params.realtime(), &params.output(), num_leds)
+            });
+            input
+                .listen(protocol)
+                .map_err(|e| format!("Failed to listen for {}: {}", protocol, e))?;
+            println!("Listening for {} on port {}.", protocol, protocol.port());
+        } else {
+            return Err(format!(
+                "Unknown protocol `{}`. Expected artnet, sacn, ddp or wled.",
+                name
+            ));
+        }
+        args.drain(at..at + 2);
+    }
+
+    let mut inputs: Vec<Box<dyn NetworkInput>> = vec![];
+    if let Some(dmx) = dmx {
+        inputs.push(Box::new(dmx));
+    }
+    if let Some(realtime) = realtime {
+        inputs.push(Box::new(realtime));
     }
-    controller.render().unwrap();
+    Ok(inputs)
+}
+
+/// Forwards each line typed into stdin, so effects can be switched without restarting.
+fn read_effect_requests() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            let name = line.trim();
+            if !name.is_empty() && sender.send(name.to_string()).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
 }
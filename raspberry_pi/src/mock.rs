@@ -0,0 +1,168 @@
+use std::{
+    os::raw::c_uint,
+    thread,
+    time::{Duration, Instant},
+};
+
+use driver_examples::wiring::Channel;
+use rs_ws281x::{RawColor, Result};
+
+use crate::controller::{strip_type, LedController};
+
+// the same timing rs_ws281x works with: 800kHz data, then a reset gap
+const BIT_TIME: Duration = Duration::from_nanos(1250);
+const RESET_TIME: Duration = Duration::from_micros(55);
+// set in the strip types that have a white channel
+const WHITE_MASK: c_uint = 0xf000_0000;
+
+/// A line as rs_ws281x would set it up.
+#[derive(Clone, Debug)]
+pub struct MockChannel {
+    pub pin: u8,
+    pub strip_type: rs_ws281x::StripType,
+    leds: Vec<RawColor>,
+}
+
+impl MockChannel {
+    /// The bytes this line's LEDs would be sent, in wire order.
+    pub fn wire_bytes(&self, leds: &[RawColor]) -> Vec<u8> {
+        let layout: c_uint = self.strip_type.into();
+        // rs_ws281x sends the byte at the red shift first, then green, blue and white
+        let shifts = [16, 8, 0, 24].map(|at| (layout >> at) & 0xff);
+        let count = self.bytes_per_led();
+
+        leds.iter()
+            .flat_map(|led| {
+                let value = u32::from_ne_bytes(*led);
+                shifts[..count]
+                    .iter()
+                    .map(move |shift| (value >> shift) as u8)
+            })
+            .collect()
+    }
+
+    /// How long rs_ws281x takes to send this line a frame.
+    pub fn transfer_time(&self) -> Duration {
+        BIT_TIME * (self.leds.len() * self.bytes_per_led() * 8) as u32 + RESET_TIME
+    }
+
+    pub fn bytes_per_led(&self) -> usize {
+        let layout: c_uint = self.strip_type.into();
+        match layout & WHITE_MASK {
+            0 => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// Everything a single `render` call sent.
+#[derive(Clone, Debug)]
+pub struct Render {
+    pub at: Instant,
+    /// Each channel's LEDs as `0xWWRRGGBB` in native byte order, like rs_ws281x gets them.
+    pub leds: Vec<Vec<RawColor>>,
+}
+
+/// An in-memory stand-in for `rs_ws281x::Controller` that records every render, for
+/// running the Pi runtime without GPIO.
+///
+/// By default rendering waits for the previous frame's transfer to finish, like the real
+/// controller does, so the runtime is paced the same as on hardware.
+#[derive(Clone, Debug)]
+pub struct MockController {
+    channels: Vec<MockChannel>,
+    renders: Vec<Render>,
+    keep: Option<usize>,
+    timing: bool,
+    busy_until: Option<Instant>,
+}
+
+impl MockController {
+    pub fn new(channels: &[Channel]) -> Self {
+        let channels = channels
+            .iter()
+            .map(|channel| MockChannel {
+                pin: channel.pin,
+                strip_type: strip_type(&channel.strip),
+                leds: vec![[0; 4]; channel.pixels.len()],
+            })
+            .collect();
+
+        MockController {
+            channels,
+            renders: vec![],
+            keep: None,
+            timing: true,
+            busy_until: None,
+        }
+    }
+
+    /// Whether `render` waits out each transfer, as real hardware would.
+    pub fn with_timing(mut self, timing: bool) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Only remember the last `count` renders, for long runs.
+    pub fn keep_last(mut self, count: usize) -> Self {
+        self.keep = Some(count);
+        self
+    }
+
+    pub fn channels(&self) -> &[MockChannel] {
+        &self.channels
+    }
+
+    pub fn renders(&self) -> &[Render] {
+        &self.renders
+    }
+
+    pub fn last_render(&self) -> Option<&Render> {
+        self.renders.last()
+    }
+
+    /// The bytes the last render sent down `channel`, in wire order.
+    pub fn last_wire_bytes(&self, channel: usize) -> Option<Vec<u8>> {
+        let render = self.renders.last()?;
+        Some(self.channels[channel].wire_bytes(&render.leds[channel]))
+    }
+
+    /// How long a frame takes to send, limited by the longest line since they're sent
+    /// in parallel.
+    pub fn transfer_time(&self) -> Duration {
+        self.channels
+            .iter()
+            .map(MockChannel::transfer_time)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl LedController for MockController {
+    fn leds_mut(&mut self, channel: usize) -> &mut [RawColor] {
+        &mut self.channels[channel].leds
+    }
+
+    fn render(&mut self) -> Result<()> {
+        if self.timing {
+            // like the real controller, wait for the previous frame to go out first
+            if let Some(wait) = self
+                .busy_until
+                .and_then(|t| t.checked_duration_since(Instant::now()))
+            {
+                thread::sleep(wait);
+            }
+            self.busy_until = Some(Instant::now() + self.transfer_time());
+        }
+
+        self.renders.push(Render {
+            at: Instant::now(),
+            leds: self.channels.iter().map(|c| c.leds.clone()).collect(),
+        });
+        if let Some(keep) = self.keep {
+            let excess = self.renders.len().saturating_sub(keep);
+            self.renders.drain(..excess);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use driver_examples::{
+    net::input::NetworkInput,
+    params::ParamFile,
+    playlist::Playlist,
+    post::PostProcess,
+    power::{PowerEstimate, PowerLimiter},
+    registry::{self, EffectInfo},
+    sanitize::Sanitizer,
+    sink::{Frame, LedSink},
+    transition::Crossfader,
+};
+use spatial_led::{SledError, Vec2};
+
+/// What happened during one step of the runtime.
+#[derive(Clone, Copy, Debug)]
+pub struct Step {
+    pub estimate: PowerEstimate,
+    /// The effect the playlist moved on to this step, if it did.
+    pub started: Option<&'static EffectInfo>,
+}
+
+/// Plays effects on the Pi: steps them, runs every frame through sanitizing,
+/// post-processing and the power limit, then sends it to the sink.
+///
+/// Pixels received over the network are layered onto each frame after post-processing,
+/// so they still go through the power limit.
+pub struct Runtime<S: LedSink> {
+    fader: Crossfader,
+    playlist: Option<Playlist>,
+    params: ParamFile,
+    positions: Vec<Vec2>,
+    sanitizer: Sanitizer,
+    post: PostProcess,
+    power: PowerLimiter,
+    inputs: Vec<Box<dyn NetworkInput>>,
+    sink: S,
+}
+
+impl<S: LedSink> Runtime<S> {
+    pub fn new(
+        fader: Crossfader,
+        playlist: Option<Playlist>,
+        params: ParamFile,
+        power: PowerLimiter,
+        sink: S,
+    ) -> Self {
+        Runtime {
+            positions: fader.positions().collect(),
+            fader,
+            playlist,
+            post: params.post(),
+            params,
+            sanitizer: Sanitizer::default(),
+            power,
+            inputs: vec![],
+            sink,
+        }
+    }
+
+    /// Layers pixels from `input` over the effects, after any inputs already added.
+    pub fn with_input(mut self, input: impl NetworkInput + 'static) -> Self {
+        self.inputs.push(Box::new(input));
+        self
+    }
+
+    /// Crossfades into the effect called `name`.
+    pub fn switch_to(&mut self, name: &str) -> Result<(), SledError> {
+        let effect = registry::find(name)
+            .ok_or_else(|| SledError::new(format!("Unknown effect `{}`.", name)))?;
+        let driver = effect.build_driver_with(&self.params)?;
+        self.fader.switch_to(driver);
+        Ok(())
+    }
+
+    pub fn step(&mut self) -> Result<Step, SledError> {
+        let mut started = None;
+        if let Some(playlist) = &mut self.playlist {
+            if let Some(entry) = playlist.update(&mut self.fader, &self.params)? {
+                started = Some(entry.effect);
+            }
+        }
+
+        self.fader.step();
+        // clamping never fails, so a bad frame can't take the lights down
+        let _ = self.sanitizer.apply(self.fader.colors_mut());
+        self.post.apply(self.fader.colors_mut());
+        for input in &mut self.inputs {
+            input.poll()?;
+            input.apply(self.fader.colors_mut());
+        }
+        let estimate = self.power.apply(self.fader.colors_mut());
+
+        let frame = Frame::new(self.fader.output(), &self.positions);
+        self.sink.send(&frame)?;
+        Ok(Step { estimate, started })
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+}
@@ -0,0 +1,55 @@
+use driver_examples::{
+    encode::Encoder,
+    params::ParamFile,
+    sink::{Frame, LedSink},
+    wiring::Wiring,
+};
+use spatial_led::{SledError, SledResult};
+
+use crate::controller::LedController;
+
+/// Sends frames down the Pi's data lines.
+pub struct GpioSink<C: LedController> {
+    controller: C,
+    wiring: Wiring,
+    // one per line, since dithering is tracked per pixel
+    encoders: Vec<Encoder>,
+}
+
+impl<C: LedController> GpioSink<C> {
+    pub fn new(controller: C, wiring: Wiring, params: &ParamFile) -> Self {
+        let encoders = wiring
+            .channels()
+            .iter()
+            .map(|channel| Encoder::new(&params.output()).with_white(channel.strip.white()))
+            .collect();
+        GpioSink {
+            controller,
+            wiring,
+            encoders,
+        }
+    }
+
+    pub fn controller(&self) -> &C {
+        &self.controller
+    }
+}
+
+impl<C: LedController> LedSink for GpioSink<C> {
+    fn send(&mut self, frame: &Frame) -> SledResult {
+        let lines = self.wiring.channels().iter().zip(&mut self.encoders);
+        for (i, (channel, encoder)) in lines.enumerate() {
+            let leds = self.controller.leds_mut(i);
+            let colors = encoder.encode(channel.colors(frame.colors));
+
+            // rs_ws281x takes each led as 0xWWRRGGBB in native byte order, and reorders
+            // the channels for the strip itself
+            for (led, [r, g, b, w]) in leds.iter_mut().zip(colors) {
+                *led = [*b, *g, *r, *w];
+            }
+        }
+        self.controller
+            .render()
+            .map_err(|e| SledError::new(format!("Failed to render: {}", e)))
+    }
+}
@@ -0,0 +1,196 @@
+use std::{net::UdpSocket, path::PathBuf, thread, time::Duration};
+
+use driver_examples::{
+    clock,
+    net::{artnet, dmx::DmxProtocol, input::DmxInput},
+    params::ParamFile,
+    power::PowerLimiter,
+    registry,
+    sink::{Frame, LedSink},
+    transition::{Blend, Crossfader},
+    wiring::Wiring,
+};
+use palette::rgb::Rgb;
+use raspberry_pi::{mock::MockController, runtime::Runtime, sink::GpioSink};
+use spatial_led::{Sled, Vec2};
+
+// linear output without dithering, so bytes can be checked exactly
+const EXACT: &str = "[output]\ndither = false\n";
+
+fn room() -> Sled<Rgb> {
+    let room = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.yap");
+    Sled::new(room.to_str().unwrap()).unwrap()
+}
+
+fn mock_sink(sled: &Sled<Rgb>, params: &str) -> GpioSink<MockController> {
+    let params = ParamFile::parse(params).unwrap();
+    let wiring = Wiring::new(&params.wiring(), &params.strip(), sled).unwrap();
+    let controller = MockController::new(wiring.channels()).with_timing(false);
+    GpioSink::new(controller, wiring, &params)
+}
+
+fn send(sink: &mut impl LedSink, sled: &Sled<Rgb>, colors: &[Rgb]) {
+    let positions: Vec<Vec2> = sled.positions().collect();
+    sink.send(&Frame::new(colors, &positions)).unwrap();
+}
+
+#[test]
+fn sends_channels_in_the_strip_order() {
+    let sled = room();
+    let mut colors = vec![Rgb::new(0.0, 0.0, 0.0); sled.num_leds()];
+    colors[0] = Rgb::new(1.0, 0.5, 0.0);
+
+    for (order, expected) in [
+        ("grb", [128, 255, 0]),
+        ("rgb", [255, 128, 0]),
+        ("gbr", [128, 0, 255]),
+        ("bgr", [0, 128, 255]),
+    ] {
+        let mut sink = mock_sink(&sled, &format!("{}[strip]\norder = \"{}\"\n", EXACT, order));
+        send(&mut sink, &sled, &colors);
+
+        let bytes = sink.controller().last_wire_bytes(0).unwrap();
+        assert_eq!(bytes.len(), sled.num_leds() * 3);
+        assert_eq!(bytes[..3], expected, "{}", order);
+    }
+}
+
+#[test]
+fn rgbw_strips_send_white_last() {
+    let sled = room();
+    let colors = vec![Rgb::new(1.0, 1.0, 1.0); sled.num_leds()];
+    let mut sink = mock_sink(
+        &sled,
+        &format!(
+            "{}[strip]\ntype = \"sk6812w\"\nwhite_temperature = 6600\n",
+            EXACT
+        ),
+    );
+    send(&mut sink, &sled, &colors);
+
+    let bytes = sink.controller().last_wire_bytes(0).unwrap();
+    assert_eq!(bytes.len(), sled.num_leds() * 4);
+    assert_eq!(bytes[..4], [0, 0, 0, 255]);
+}
+
+#[test]
+fn applies_gamma_before_sending() {
+    let sled = room();
+    let colors = vec![Rgb::new(0.5, 0.5, 0.5); sled.num_leds()];
+    let mut sink = mock_sink(&sled, "[output]\ngamma = 2.0\ndither = false\n");
+    send(&mut sink, &sled, &colors);
+
+    let bytes = sink.controller().last_wire_bytes(0).unwrap();
+    assert_eq!(bytes[..3], [64, 64, 64]);
+}
+
+#[test]
+fn follows_the_wiring_map() {
+    let sled = room();
+    let segment = |s: usize| -> Vec<usize> {
+        sled.leds()
+            .filter(|led| led.segment() as usize == s)
+            .map(|led| led.index() as usize)
+            .collect()
+    };
+    let colors: Vec<Rgb> = (0..sled.num_leds())
+        .map(|i| Rgb::new(i as f32 / 255.0, 0.0, 0.0))
+        .collect();
+
+    let mut sink = mock_sink(
+        &sled,
+        &format!(
+            "{}[strip]\norder = \"rgb\"\n\n\
+             [[wiring.channels]]\npin = 18\nsegments = [0]\ndead = [[0, 1]]\n\n\
+             [[wiring.channels]]\npin = 13\nsegments = [1]\nreversed = [1]\n",
+            EXACT
+        ),
+    );
+    send(&mut sink, &sled, &colors);
+
+    let controller = sink.controller();
+    assert_eq!(controller.channels()[0].pin, 18);
+    assert_eq!(controller.channels()[1].pin, 13);
+
+    // the red byte of each pixel is its led index
+    let reds = |channel: usize| -> Vec<usize> {
+        let bytes = controller.last_wire_bytes(channel).unwrap();
+        bytes.chunks(3).map(|pixel| pixel[0] as usize).collect()
+    };
+    let mut first = vec![0];
+    first.extend(segment(0));
+    assert_eq!(reds(0), first);
+    assert_eq!(reds(1), segment(1).into_iter().rev().collect::<Vec<_>>());
+}
+
+#[test]
+fn renders_once_per_step_at_the_hardware_pace() {
+    clock::freeze();
+    let sled = room();
+    let params = ParamFile::parse(EXACT).unwrap();
+    let power = PowerLimiter::new(params.power(), &sled).unwrap();
+    let wiring = Wiring::new(&params.wiring(), &params.strip(), &sled).unwrap();
+    let controller = MockController::new(wiring.channels());
+    let frame_time = controller.transfer_time();
+    let sink = GpioSink::new(controller, wiring, &params);
+
+    let driver = registry::find("comet")
+        .unwrap()
+        .build_driver_with(&params)
+        .unwrap();
+    let fader = Crossfader::new(sled, driver, Blend::Linear, Duration::ZERO);
+    let mut runtime = Runtime::new(fader, None, params, power, sink);
+
+    for _ in 0..5 {
+        clock::advance(Duration::from_millis(16));
+        runtime.step().unwrap();
+    }
+    runtime.switch_to("ripples").unwrap();
+    assert!(runtime.switch_to("nonexistent").is_err());
+    runtime.step().unwrap();
+
+    let renders = runtime.sink().controller().renders();
+    assert_eq!(renders.len(), 6);
+    for pair in renders.windows(2) {
+        assert!(pair[1].at - pair[0].at >= frame_time);
+    }
+    // comet lights something up within a few frames
+    assert!(renders[4].leds[0].iter().any(|led| led != &[0; 4]));
+}
+
+#[test]
+fn network_input_replaces_the_effect() {
+    let sled = room();
+    let params = ParamFile::parse(&format!(
+        "{}[strip]\norder = \"rgb\"\n[dmx]\nlayer = \"replace\"\n",
+        EXACT
+    ))
+    .unwrap();
+    let power = PowerLimiter::new(params.power(), &sled).unwrap();
+    let mut input = DmxInput::new(params.dmx(), &params.output());
+    let address = input.bind(DmxProtocol::ArtNet, "127.0.0.1:0").unwrap();
+    let sink = mock_sink(&sled, &params.to_string());
+
+    let driver = registry::find("comet")
+        .unwrap()
+        .build_driver_with(&params)
+        .unwrap();
+    let fader = Crossfader::new(sled, driver, Blend::Linear, Duration::ZERO);
+    let mut runtime = Runtime::new(fader, None, params, power, sink).with_input(input);
+
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender
+        .send_to(
+            &artnet::dmx_packet(1, 1, &[10, 20, 30, 40, 50, 60]),
+            address,
+        )
+        .unwrap();
+    // give the packet a moment to arrive over loopback
+    thread::sleep(Duration::from_millis(20));
+    runtime.step().unwrap();
+
+    let bytes = runtime.sink().controller().last_wire_bytes(0).unwrap();
+    assert_eq!(bytes[..6], [10, 20, 30, 40, 50, 60]);
+    // everything no universe covers stays dark
+    assert!(bytes[6..].iter().all(|byte| *byte == 0));
+}